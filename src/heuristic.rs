@@ -0,0 +1,432 @@
+use std::cmp::{Ordering, Reverse};
+
+use var::{VarSet, VarId, Variable, OrderedVariable, Integer};
+use solve::Decision;
+
+/// Chooses the variable to branch on at each node of the search.
+pub trait VarSelector<V>: Send
+where
+    V: Variable,
+{
+    /// Picks a variable whose domain still has more than one value, or
+    /// returns `None` if every variable is fixed.
+    fn select(&mut self, vars: &VarSet<V>) -> Option<VarId>;
+
+    fn boxed_clone(&self) -> Box<dyn VarSelector<V>>;
+}
+
+impl<V> Clone for Box<dyn VarSelector<V>>
+where
+    V: Variable,
+{
+    fn clone(&self) -> Box<dyn VarSelector<V>> {
+        self.boxed_clone()
+    }
+}
+
+/// The variables that still need to be branched on, in input order.
+fn unfixed<'a, V>(vars: &'a VarSet<V>) -> impl Iterator<Item = VarId> + 'a
+where
+    V: Variable,
+{
+    vars.var_ids()
+        .iter()
+        .cloned()
+        .filter(move |&id| vars.var(id).size() > 1)
+}
+
+/// The number of propagators watching a variable.
+fn degree<V>(vars: &VarSet<V>, var_id: VarId) -> usize
+where
+    V: Variable,
+{
+    vars.subscriptions(var_id).len()
+}
+
+/// Branches on variables in the order they were created.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InputOrder;
+
+impl<V> VarSelector<V> for InputOrder
+where
+    V: Variable,
+{
+    fn select(&mut self, vars: &VarSet<V>) -> Option<VarId> {
+        unfixed(vars).next()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn VarSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Branches on the variable with the smallest domain. This is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FirstFail;
+
+impl<V> VarSelector<V> for FirstFail
+where
+    V: Variable,
+{
+    fn select(&mut self, vars: &VarSet<V>) -> Option<VarId> {
+        unfixed(vars).min_by_key(|&id| vars.var(id).size())
+    }
+
+    fn boxed_clone(&self) -> Box<dyn VarSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Branches on the variable involved in the most propagators, breaking ties
+/// by smallest domain.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MostConstrained;
+
+impl<V> VarSelector<V> for MostConstrained
+where
+    V: Variable,
+{
+    fn select(&mut self, vars: &VarSet<V>) -> Option<VarId> {
+        unfixed(vars).min_by_key(|&id| (Reverse(degree(vars, id)), vars.var(id).size()))
+    }
+
+    fn boxed_clone(&self) -> Box<dyn VarSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Branches on the variable with the smallest ratio of domain size to the
+/// number of propagators involving it. Unconstrained variables come last.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DomOverDeg;
+
+impl<V> VarSelector<V> for DomOverDeg
+where
+    V: Variable,
+{
+    fn select(&mut self, vars: &VarSet<V>) -> Option<VarId> {
+        let ratio = |id: VarId| (vars.var(id).size(), degree(vars, id));
+        unfixed(vars).min_by(|&a, &b| {
+            let (size_a, deg_a) = ratio(a);
+            let (size_b, deg_b) = ratio(b);
+            match (deg_a, deg_b) {
+                (0, 0) => size_a.cmp(&size_b),
+                (0, _) => Ordering::Greater,
+                (_, 0) => Ordering::Less,
+                _ => (size_a * deg_b).cmp(&(size_b * deg_a)),
+            }
+        })
+    }
+
+    fn boxed_clone(&self) -> Box<dyn VarSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Branches on the variable with the largest domain.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LargestDomain;
+
+impl<V> VarSelector<V> for LargestDomain
+where
+    V: Variable,
+{
+    fn select(&mut self, vars: &VarSet<V>) -> Option<VarId> {
+        unfixed(vars).min_by_key(|&id| Reverse(vars.var(id).size()))
+    }
+
+    fn boxed_clone(&self) -> Box<dyn VarSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Makes uniformly random choices from a seeded xorshift generator, so a
+/// search with the same seed is reproducible.
+#[derive(Clone, Copy, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        // xorshift gets stuck on an all-zero state, which one seed maps to
+        const MIX: u64 = 0x9e37_79b9_7f4a_7c15;
+        let state = seed ^ MIX;
+        Random {
+            state: if state == 0 { MIX } else { state },
+        }
+    }
+
+    /// A random number in `0..n`. `n` must not be zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state % n as u64) as usize
+    }
+}
+
+impl<V> VarSelector<V> for Random
+where
+    V: Variable,
+{
+    fn select(&mut self, vars: &VarSet<V>) -> Option<VarId> {
+        let candidates = unfixed(vars).collect::<Vec<_>>();
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(candidates[self.below(candidates.len())])
+        }
+    }
+
+    fn boxed_clone(&self) -> Box<dyn VarSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Chooses the branch to explore first for the variable picked by the
+/// `VarSelector`. With `BinaryBranching` the solver branches on the returned
+/// decision and then on its negation.
+pub trait ValueSelector<V>: Send
+where
+    V: Variable,
+{
+    /// Picks the value to try first. `var` always has more than one value.
+    fn select(&mut self, var: &V) -> V::Value;
+
+    /// The decision for the first branch, by default assigning the value
+    /// from `select`.
+    fn decision(&mut self, var: &V) -> Decision<V> {
+        Decision::assign(var.id(), self.select(var))
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ValueSelector<V>>;
+}
+
+impl<V> Clone for Box<dyn ValueSelector<V>>
+where
+    V: Variable,
+{
+    fn clone(&self) -> Box<dyn ValueSelector<V>> {
+        self.boxed_clone()
+    }
+}
+
+/// Any cloneable closure from a variable to one of its values can be used as
+/// a value selector.
+impl<V, F> ValueSelector<V> for F
+where
+    V: Variable,
+    F: FnMut(&V) -> V::Value + Clone + Send + 'static,
+{
+    fn select(&mut self, var: &V) -> V::Value {
+        self(var)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ValueSelector<V>> {
+        Box::new(self.clone())
+    }
+}
+
+/// Tries the first value the variable's domain yields. This is the default,
+/// and the only built-in selector that works for unordered domains.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FirstValue;
+
+impl<V> ValueSelector<V> for FirstValue
+where
+    V: Variable,
+{
+    fn select(&mut self, var: &V) -> V::Value {
+        var.possibilities().next().unwrap()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ValueSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Tries the smallest value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinValue;
+
+impl<V> ValueSelector<V> for MinValue
+where
+    V: OrderedVariable,
+{
+    fn select(&mut self, var: &V) -> V::Value {
+        var.min().clone()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ValueSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Tries the largest value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaxValue;
+
+impl<V> ValueSelector<V> for MaxValue
+where
+    V: OrderedVariable,
+{
+    fn select(&mut self, var: &V) -> V::Value {
+        var.max().clone()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ValueSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Tries the middle value of the domain, the lower one if the domain has an
+/// even number of values. Takes time linear in the size of the domain.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MedianValue;
+
+impl<V> ValueSelector<V> for MedianValue
+where
+    V: OrderedVariable,
+{
+    fn select(&mut self, var: &V) -> V::Value {
+        var.possibilities().nth((var.size() - 1) / 2).unwrap()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ValueSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+impl<V> ValueSelector<V> for Random
+where
+    V: Variable,
+{
+    fn select(&mut self, var: &V) -> V::Value {
+        let index = self.below(var.size());
+        var.possibilities().nth(index).unwrap()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ValueSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Instead of trying a single value, first restricts the variable to the
+/// lower half of its range, `x <= (min + max) / 2`, then to the upper half.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SplitLowerHalf;
+
+impl<V> ValueSelector<V> for SplitLowerHalf
+where
+    V: OrderedVariable,
+    V::Value: Integer,
+{
+    fn select(&mut self, var: &V) -> V::Value {
+        let (min, max) = (var.min().to_i64(), var.max().to_i64());
+        V::Value::from_i64(min + (max - min) / 2)
+    }
+
+    fn decision(&mut self, var: &V) -> Decision<V> {
+        Decision::at_most(var.id(), self.select(var))
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ValueSelector<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Turns the variable chosen for a choice point into the alternatives the
+/// search explores below it.
+pub trait Branching<V>: Send
+where
+    V: Variable,
+{
+    /// The alternatives for `var`, in the order they are explored. Together
+    /// they must cover every value in its domain.
+    fn branch(&mut self, var: &V, values: &mut dyn ValueSelector<V>) -> Vec<Decision<V>>;
+
+    fn boxed_clone(&self) -> Box<dyn Branching<V>>;
+}
+
+impl<V> Clone for Box<dyn Branching<V>>
+where
+    V: Variable,
+{
+    fn clone(&self) -> Box<dyn Branching<V>> {
+        self.boxed_clone()
+    }
+}
+
+/// Branches on the value selector's decision and then its negation, such as
+/// `x = v` and `x != v`. This is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BinaryBranching;
+
+impl<V> Branching<V> for BinaryBranching
+where
+    V: Variable,
+{
+    fn branch(&mut self, var: &V, values: &mut dyn ValueSelector<V>) -> Vec<Decision<V>> {
+        let decision = values.decision(var);
+        vec![decision.clone(), decision.negate()]
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Branching<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Creates one child per value in the domain, starting with the value from
+/// the value selector and continuing in domain order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DWayBranching;
+
+impl<V> Branching<V> for DWayBranching
+where
+    V: Variable,
+    V::Value: PartialEq,
+{
+    fn branch(&mut self, var: &V, values: &mut dyn ValueSelector<V>) -> Vec<Decision<V>> {
+        let first = values.select(var);
+        let mut decisions = vec![Decision::assign(var.id(), first.clone())];
+        decisions.extend(
+            var.possibilities()
+                .filter(|value| *value != first)
+                .map(|value| Decision::assign(var.id(), value)),
+        );
+        decisions
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Branching<V>> {
+        Box::new(*self)
+    }
+}
+
+/// Splits the domain in two halves, `x <= mid` and `x > mid` where `mid` is
+/// the middle of its bounds, so large domains are narrowed down in a
+/// logarithmic number of steps. The half containing the value from the value
+/// selector is explored first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bisection;
+
+impl<V> Branching<V> for Bisection
+where
+    V: OrderedVariable,
+    V::Value: Integer,
+{
+    fn branch(&mut self, var: &V, values: &mut dyn ValueSelector<V>) -> Vec<Decision<V>> {
+        let (min, max) = (var.min().to_i64(), var.max().to_i64());
+        let mid = V::Value::from_i64(min + (max - min) / 2);
+        let lower = Decision::at_most(var.id(), mid);
+        if values.select(var) <= mid {
+            vec![lower.clone(), lower.negate()]
+        } else {
+            vec![lower.negate(), lower]
+        }
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Branching<V>> {
+        Box::new(*self)
+    }
+}
@@ -1,11 +1,18 @@
 pub mod var;
 pub mod propagate;
 pub mod solve;
+pub mod heuristic;
+pub mod trail;
+pub mod solution;
 
 use std::borrow::Borrow;
+use std::hash::Hash;
 
-use var::{VarSet, Variable, VarId};
-use propagate::{PropSet, PropId, Propagate};
+use var::{VarSet, Variable, OrderedVariable, Integer, VarId};
+use propagate::{PropSet, PropId, Propagate, Relation, AllDifferentConsistency};
+use propagate::{Table, TableKind, Element, ElementEntry};
+use propagate::{Regular, Automaton, GlobalCardinality, Occurrences};
+use propagate::{Cumulative, Disjunctive, Task};
 use solve::Solver;
 
 pub struct Model<V>
@@ -16,6 +23,15 @@ where
     prop_set: PropSet<V>,
 }
 
+impl<V> Default for Model<V>
+where
+    V: Variable,
+{
+    fn default() -> Model<V> {
+        Model::new()
+    }
+}
+
 impl<V> Model<V>
 where
     V: Variable,
@@ -72,21 +88,310 @@ where
         var_ids
     }
 
+    /// Like `create_var`, but gives the variable a name that shows up in
+    /// solutions and failure descriptions.
+    pub fn create_named_var<S, I, Q>(&mut self, name: S, values: I) -> VarId
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<V::Value>,
+    {
+        let var_id = self.create_var(values);
+        self.var_set.set_name(var_id, name);
+        var_id
+    }
+
+    /// Like `create_var_array`, naming the variables `name[i]`.
+    pub fn create_named_var_array<I, Q>(&mut self, name: &str, values: I, len: usize) -> Vec<VarId>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<V::Value>,
+    {
+        let var_ids = self.create_var_array(values, len);
+        for (i, &var_id) in var_ids.iter().enumerate() {
+            self.var_set.set_name(var_id, format!("{}[{}]", name, i));
+        }
+        var_ids
+    }
+
+    /// Like `create_var_matrix`, naming the variables `name[r][c]`.
+    pub fn create_named_var_matrix<I, Q>(
+        &mut self,
+        name: &str,
+        values: I,
+        rows: usize,
+        cols: usize,
+    ) -> Vec<Vec<VarId>>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<V::Value>,
+    {
+        let var_ids = self.create_var_matrix(values, rows, cols);
+        for (r, row) in var_ids.iter().enumerate() {
+            for (c, &var_id) in row.iter().enumerate() {
+                self.var_set.set_name(var_id, format!("{}[{}][{}]", name, r, c));
+            }
+        }
+        var_ids
+    }
+
+    pub fn name_var<S>(&mut self, var_id: VarId, name: S)
+    where
+        S: Into<String>,
+    {
+        self.var_set.set_name(var_id, name);
+    }
+
+    pub fn var_name(&self, var_id: VarId) -> Option<&str> {
+        self.var_set.name(var_id)
+    }
+
+    /// Labels a propagator, for example with the rule of the problem it
+    /// models, so that it can be recognised in statistics and failures.
+    pub fn label<S>(&mut self, prop_id: PropId, label: S)
+    where
+        S: Into<String>,
+    {
+        self.prop_set.set_label(prop_id, label);
+    }
+
+    pub fn prop_label(&self, prop_id: PropId) -> Option<&str> {
+        self.prop_set.label(prop_id)
+    }
+
     pub fn set(&mut self, var_id: VarId, value: &V::Value) {
         self.var_set.set(var_id, value)
     }
 
-    pub fn all_different<I, Q>(&mut self, var_ids: I) -> PropId
+    /// Constrains the variables to take pairwise different values, filtered
+    /// as strongly as `consistency` asks for: `ValueElimination`,
+    /// `BoundsConsistency` or `DomainConsistency`.
+    pub fn all_different<I, Q, C>(&mut self, var_ids: I, consistency: C) -> PropId
     where
         I: IntoIterator<Item = Q>,
         Q: Borrow<VarId>,
-        V: 'static,
+        C: AllDifferentConsistency<V>,
+    {
+        let var_ids = var_ids.into_iter().map(|id| *id.borrow()).collect();
+        self.prop_set.add_propagator(consistency.propagator(var_ids))
+    }
+
+    /// Constrains `sum(a_i * x_i) <relation> constant`, where `terms` are the
+    /// `(a_i, x_i)` pairs.
+    pub fn linear<I, Q>(&mut self, terms: I, relation: Relation, constant: i64) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<(i64, VarId)>,
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
     {
         self.prop_set.add_propagator(
-            propagate::AllDifferent::new(var_ids),
+            propagate::Linear::new(terms, relation, constant),
         )
     }
 
+    pub fn linear_eq<I, Q>(&mut self, terms: I, constant: i64) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<(i64, VarId)>,
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.linear(terms, Relation::Eq, constant)
+    }
+
+    pub fn linear_ne<I, Q>(&mut self, terms: I, constant: i64) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<(i64, VarId)>,
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.linear(terms, Relation::Ne, constant)
+    }
+
+    pub fn linear_le<I, Q>(&mut self, terms: I, constant: i64) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<(i64, VarId)>,
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.linear(terms, Relation::Le, constant)
+    }
+
+    pub fn linear_ge<I, Q>(&mut self, terms: I, constant: i64) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<(i64, VarId)>,
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.linear(terms, Relation::Ge, constant)
+    }
+
+    /// Constrains `x <relation> y + offset`.
+    pub fn binary(&mut self, x: VarId, relation: Relation, y: VarId, offset: i64) -> PropId
+    where
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.prop_set.add_propagator(propagate::Binary::new(x, relation, y, offset))
+    }
+
+    pub fn binary_eq(&mut self, x: VarId, y: VarId, offset: i64) -> PropId
+    where
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.binary(x, Relation::Eq, y, offset)
+    }
+
+    pub fn binary_ne(&mut self, x: VarId, y: VarId, offset: i64) -> PropId
+    where
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.binary(x, Relation::Ne, y, offset)
+    }
+
+    pub fn binary_lt(&mut self, x: VarId, y: VarId, offset: i64) -> PropId
+    where
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.binary(x, Relation::Lt, y, offset)
+    }
+
+    pub fn binary_le(&mut self, x: VarId, y: VarId, offset: i64) -> PropId
+    where
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.binary(x, Relation::Le, y, offset)
+    }
+
+    pub fn binary_gt(&mut self, x: VarId, y: VarId, offset: i64) -> PropId
+    where
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.binary(x, Relation::Gt, y, offset)
+    }
+
+    pub fn binary_ge(&mut self, x: VarId, y: VarId, offset: i64) -> PropId
+    where
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.binary(x, Relation::Ge, y, offset)
+    }
+
+    /// Constrains `var_ids` to take the values of one of `tuples`.
+    pub fn table<I, Q, J, T>(&mut self, var_ids: I, tuples: J) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+        J: IntoIterator<Item = T>,
+        T: AsRef<[V::Value]>,
+        V: 'static,
+        V::Value: Hash + Eq,
+    {
+        self.prop_set.add_propagator(Table::new(var_ids, tuples, TableKind::Allowed))
+    }
+
+    /// Constrains `var_ids` to take values other than those of `tuples`.
+    pub fn forbidden_table<I, Q, J, T>(&mut self, var_ids: I, tuples: J) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+        J: IntoIterator<Item = T>,
+        T: AsRef<[V::Value]>,
+        V: 'static,
+        V::Value: Hash + Eq,
+    {
+        self.prop_set.add_propagator(Table::new(var_ids, tuples, TableKind::Forbidden))
+    }
+
+    /// Constrains `result = array[index]`, where `index` counts from 0.
+    pub fn element<I, Q>(&mut self, index: VarId, array: I, result: VarId) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+        V: 'static,
+        V::Value: Integer,
+    {
+        let array = array
+            .into_iter()
+            .map(|var_id| ElementEntry::Var(*var_id.borrow()))
+            .collect();
+        self.prop_set.add_propagator(Element::new(index, array, result))
+    }
+
+    /// Constrains `result = values[index]`, where `index` counts from 0.
+    pub fn element_values<I, Q>(&mut self, index: VarId, values: I, result: VarId) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<V::Value>,
+        V: 'static,
+        V::Value: Integer,
+    {
+        let array = values
+            .into_iter()
+            .map(|value| ElementEntry::Value(*value.borrow()))
+            .collect();
+        self.prop_set.add_propagator(Element::new(index, array, result))
+    }
+
+    /// Constrains the values of `var_ids`, in order, to spell a word that
+    /// `automaton` accepts.
+    pub fn regular<I, Q>(&mut self, var_ids: I, automaton: Automaton<V::Value>) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+        V: 'static,
+        V::Value: Hash + Eq,
+    {
+        self.prop_set.add_propagator(Regular::new(var_ids, automaton))
+    }
+
+    /// Constrains how often each value occurs among `var_ids`, as given by
+    /// the `(value, occurrences)` pairs. Values that are not listed may
+    /// occur any number of times.
+    pub fn global_cardinality<I, Q, J>(&mut self, var_ids: I, occurrences: J) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+        J: IntoIterator<Item = (V::Value, Occurrences)>,
+        V: 'static,
+        V::Value: Integer,
+    {
+        self.prop_set.add_propagator(GlobalCardinality::new(var_ids, occurrences))
+    }
+
+    /// Constrains the tasks never to use more than `capacity` units of the
+    /// resource at once.
+    pub fn cumulative<I, Q>(&mut self, tasks: I, capacity: i64) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<Task>,
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.prop_set.add_propagator(Cumulative::new(tasks, capacity))
+    }
+
+    /// Constrains the tasks never to run at the same time.
+    pub fn disjunctive<I, Q>(&mut self, tasks: I) -> PropId
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<Task>,
+        V: OrderedVariable + 'static,
+        V::Value: Integer,
+    {
+        self.prop_set.add_propagator(Disjunctive::new(tasks))
+    }
+
     pub fn add_propagator<P>(&mut self, propagator: P) -> PropId
     where
         P: Propagate<Variable = V> + 'static,
@@ -97,4 +402,72 @@ where
     pub fn solve(&self) -> Solver<V> {
         Solver::new(self.var_set.clone(), self.prop_set.clone())
     }
+
+    /// Finds a minimal set of propagators that cannot be satisfied together,
+    /// using QuickXplain. Removing any one of them makes the rest feasible.
+    /// Returns `None` if the model has a solution, and an empty set if the
+    /// domains alone have none.
+    ///
+    /// Every check runs a full search on a subset of the propagators, so this
+    /// can take much longer than solving the model once.
+    pub fn explain_infeasibility(&self) -> Option<Vec<PropId>> {
+        let all = self.prop_set.prop_ids().clone();
+        if self.is_feasible(&all) {
+            return None;
+        }
+        // Checking the empty background first also settles the case where
+        // the domains alone are infeasible.
+        let mut conflict = self.quick_xplain(&[], true, &all);
+        conflict.sort();
+        Some(conflict)
+    }
+
+    fn is_feasible(&self, prop_ids: &[PropId]) -> bool {
+        Solver::new(self.var_set.clone(), self.prop_set.subset(prop_ids))
+            .next()
+            .is_some()
+    }
+
+    /// Returns a minimal subset of `candidates` that is infeasible together
+    /// with `background`, which must hold when called. `added` tells whether
+    /// propagators were added to `background` since it was last checked.
+    fn quick_xplain(
+        &self,
+        background: &[PropId],
+        added: bool,
+        candidates: &[PropId],
+    ) -> Vec<PropId> {
+        if candidates.is_empty() || added && !self.is_feasible(background) {
+            return vec![];
+        }
+        if candidates.len() == 1 {
+            return candidates.to_vec();
+        }
+        let (first, second) = candidates.split_at(candidates.len() / 2);
+
+        let with_first = [background, first].concat();
+        let second_conflict = self.quick_xplain(&with_first, !first.is_empty(), second);
+        let with_second = [background, &second_conflict[..]].concat();
+        let mut conflict = self.quick_xplain(&with_second, !second_conflict.is_empty(), first);
+        conflict.extend(second_conflict);
+        conflict
+    }
+
+    /// Returns a solver yielding solutions with strictly decreasing values
+    /// of `objective`, see `Solver::minimize`.
+    pub fn minimize(&self, objective: VarId) -> Solver<V>
+    where
+        V: OrderedVariable,
+    {
+        self.solve().minimize(objective)
+    }
+
+    /// Returns a solver yielding solutions with strictly increasing values
+    /// of `objective`, see `Solver::maximize`.
+    pub fn maximize(&self, objective: VarId) -> Solver<V>
+    where
+        V: OrderedVariable,
+    {
+        self.solve().maximize(objective)
+    }
 }
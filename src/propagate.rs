@@ -1,39 +1,104 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::Debug;
 use std::borrow::Borrow;
 use std::marker::PhantomData;
 
-use var::{VarSet, VarId, DomainUpdate, Variable};
+use var::{VarSet, VarId, DomainUpdate, Variable, Integer, Failure};
+use trail::Trail;
+
+mod linear;
+mod all_different;
+mod graph;
+mod binary;
+mod table;
+mod element;
+mod regular;
+mod global_cardinality;
+mod cumulative;
+mod disjunctive;
+
+pub use self::linear::Linear;
+pub use self::binary::Binary;
+pub use self::table::{Table, TableKind};
+pub use self::element::{Element, ElementEntry};
+pub use self::regular::{Regular, Automaton};
+pub use self::global_cardinality::{GlobalCardinality, Occurrences};
+pub use self::cumulative::{Cumulative, Task};
+pub use self::disjunctive::Disjunctive;
+pub use self::all_different::{
+    AllDifferentConsistency, ValueElimination, DomainConsistency, BoundsConsistency,
+    AllDifferentDomain, AllDifferentBounds,
+};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct PropId {
     id: usize,
 }
 
-pub trait Propagate {
+/// A comparison between two integer expressions, as used by arithmetic
+/// constraints.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Relation {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// The value `value` stands for, if the variables' type can hold it. Values
+/// computed in `i64`, such as bounds, are converted with this rather than
+/// `Integer::from_i64`, which saturates and would turn a value outside the
+/// type's range into one inside the domain.
+pub(crate) fn convert<T>(value: i64) -> Option<T>
+where
+    T: Integer,
+{
+    let converted = T::from_i64(value);
+    if converted.to_i64() == value {
+        Some(converted)
+    } else {
+        None
+    }
+}
+
+pub trait Propagate: Send {
     type Variable: Variable;
 
+    /// Filters the domains after `update` and returns the changes made,
+    /// ordered so that the solver handles them the same way on every run.
     fn propagate(
         &mut self,
         vars: &mut VarSet<Self::Variable>,
         update: DomainUpdate,
-    ) -> Result<HashSet<DomainUpdate>, ()>;
+    ) -> Result<BTreeSet<DomainUpdate>, Failure>;
 
     fn initial_propagation(
         &mut self,
         vars: &mut VarSet<Self::Variable>,
-    ) -> Result<HashSet<DomainUpdate>, ()>;
+    ) -> Result<BTreeSet<DomainUpdate>, Failure>;
 
-    fn boxed_clone(&self) -> Box<Propagate<Variable = Self::Variable>>;
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = Self::Variable>>;
 
     fn set_id(&mut self, id: PropId);
+
+    /// Whether the propagator keeps internal state that changes during search.
+    ///
+    /// Stateful propagators are copied onto the trail before they first run
+    /// at each search level, so backtracking restores them along with the
+    /// variables. Propagators that only read the variable domains can leave
+    /// this as `false` and are never copied.
+    fn is_stateful(&self) -> bool {
+        false
+    }
 }
 
-impl<V> Clone for Box<Propagate<Variable = V>>
+impl<V> Clone for Box<dyn Propagate<Variable = V>>
 where
     V: Variable,
 {
-    fn clone(&self) -> Box<Propagate<Variable = V>> {
+    fn clone(&self) -> Box<dyn Propagate<Variable = V>> {
         self.boxed_clone()
     }
 }
@@ -43,8 +108,19 @@ pub struct PropSet<V>
 where
     V: Variable,
 {
-    propagators: Vec<Box<Propagate<Variable = V>>>,
+    propagators: Vec<Box<dyn Propagate<Variable = V>>>,
     prop_ids: Vec<PropId>,
+    labels: Vec<Option<String>>,
+    trail: Trail<Box<dyn Propagate<Variable = V>>>,
+}
+
+impl<V> Default for PropSet<V>
+where
+    V: Variable,
+{
+    fn default() -> PropSet<V> {
+        PropSet::new()
+    }
 }
 
 impl<V> PropSet<V>
@@ -55,6 +131,8 @@ where
         PropSet {
             propagators: vec![],
             prop_ids: vec![],
+            labels: vec![],
+            trail: Trail::new(),
         }
     }
 
@@ -66,20 +144,69 @@ where
         self.prop_ids.push(prop_id);
         propagator.set_id(prop_id);
         self.propagators.push(Box::new(propagator));
+        self.labels.push(None);
         prop_id
     }
 
-    pub fn propagator(&self, prop_id: PropId) -> &Box<Propagate<Variable = V>> {
-        &self.propagators[prop_id.id]
+    pub fn set_label<S>(&mut self, prop_id: PropId, label: S)
+    where
+        S: Into<String>,
+    {
+        self.labels[prop_id.id] = Some(label.into());
     }
 
-    pub fn propagator_mut(&mut self, prop_id: PropId) -> &mut Box<Propagate<Variable = V>> {
+    pub fn label(&self, prop_id: PropId) -> Option<&str> {
+        self.labels[prop_id.id].as_deref()
+    }
+
+    /// The label of the propagator, or its `Debug` output if it has none.
+    pub fn describe(&self, prop_id: PropId) -> String {
+        match self.label(prop_id) {
+            Some(label) => label.to_string(),
+            None => format!("{:?}", prop_id),
+        }
+    }
+
+    pub fn propagator(&self, prop_id: PropId) -> &dyn Propagate<Variable = V> {
+        &*self.propagators[prop_id.id]
+    }
+
+    /// Gives mutable access to a propagator. Stateful propagators are saved
+    /// on the trail first, see `Propagate::is_stateful`.
+    pub fn propagator_mut(&mut self, prop_id: PropId) -> &mut Box<dyn Propagate<Variable = V>> {
+        if self.propagators[prop_id.id].is_stateful() {
+            self.trail.save(prop_id.id, &self.propagators[prop_id.id]);
+        }
         &mut self.propagators[prop_id.id]
     }
 
     pub fn prop_ids(&self) -> &Vec<PropId> {
         &self.prop_ids
     }
+
+    /// A new set holding copies of the given propagators. They are
+    /// renumbered in the order given, so the propagator at `prop_ids[i]`
+    /// gets the `i`th id of the new set.
+    pub fn subset(&self, prop_ids: &[PropId]) -> PropSet<V> {
+        let mut subset = PropSet::new();
+        for &prop_id in prop_ids {
+            let mut propagator = self.propagators[prop_id.id].clone();
+            let new_id = PropId { id: subset.propagators.len() };
+            propagator.set_id(new_id);
+            subset.prop_ids.push(new_id);
+            subset.propagators.push(propagator);
+            subset.labels.push(self.labels[prop_id.id].clone());
+        }
+        subset
+    }
+
+    pub fn push_level(&mut self) {
+        self.trail.push_level();
+    }
+
+    pub fn pop_level(&mut self) {
+        self.trail.pop_level(&mut self.propagators);
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -100,7 +227,7 @@ impl<V> AllDifferent<V> {
         AllDifferent {
             var_ids: hashset,
             id: PropId { id: 0 },
-            phantom: PhantomData::default(),
+            phantom: PhantomData,
         }
     }
 }
@@ -115,15 +242,15 @@ where
         &mut self,
         vars: &mut VarSet<V>,
         update: DomainUpdate,
-    ) -> Result<HashSet<DomainUpdate>, ()> {
-        let mut domain_updates = HashSet::new();
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut domain_updates = BTreeSet::new();
         if let DomainUpdate::Fixed(fixed_id) = update {
             if self.var_ids.contains(&fixed_id) {
                 // self.var_ids.remove(&fixed_id);
                 let value = vars.var(fixed_id).value().unwrap().clone();
                 for &var_id in &self.var_ids {
                     if var_id != fixed_id {
-                        domain_updates.insert(vars.var_mut(var_id).remove(&value)?);
+                        domain_updates.insert(vars.remove(var_id, &value)?);
                     }
                 }
             }
@@ -131,17 +258,20 @@ where
         Ok(domain_updates)
     }
 
-    fn initial_propagation(&mut self, vars: &mut VarSet<V>) -> Result<HashSet<DomainUpdate>, ()> {
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
         for &var_id in &self.var_ids {
             vars.subscribe(var_id, self.id);
         }
 
-        let mut domain_updates = HashSet::new();
+        let mut domain_updates = BTreeSet::new();
         for &var_id in &self.var_ids {
             if let Some(value) = vars.var(var_id).value().cloned() {
                 for &other in &self.var_ids {
                     if var_id != other {
-                        domain_updates.insert(vars.var_mut(other).remove(&value)?);
+                        domain_updates.insert(vars.remove(other, &value)?);
                     }
                 }
             }
@@ -149,7 +279,7 @@ where
         Ok(domain_updates)
     }
 
-    fn boxed_clone(&self) -> Box<Propagate<Variable = V>> {
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
         Box::new((*self).clone())
     }
 
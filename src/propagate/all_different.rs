@@ -0,0 +1,607 @@
+use std::collections::{HashMap, BTreeSet, HashSet};
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use var::{VarSet, VarId, DomainUpdate, Variable, OrderedVariable, Integer, Failure};
+use propagate::{Propagate, PropId, AllDifferent, convert};
+use propagate::graph::{hinted, components};
+
+/// How strongly `Model::all_different` filters, chosen by the propagator it
+/// builds.
+pub trait AllDifferentConsistency<V>
+where
+    V: Variable,
+{
+    type Propagator: Propagate<Variable = V> + 'static;
+
+    fn propagator(&self, var_ids: Vec<VarId>) -> Self::Propagator;
+}
+
+/// Removes the value of a variable from the others once it is fixed. Cheap,
+/// but only notices conflicts between fixed variables.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct ValueElimination;
+
+impl<V> AllDifferentConsistency<V> for ValueElimination
+where
+    V: Variable + 'static,
+{
+    type Propagator = AllDifferent<V>;
+
+    fn propagator(&self, var_ids: Vec<VarId>) -> AllDifferent<V> {
+        AllDifferent::new(var_ids)
+    }
+}
+
+/// Removes every value that cannot be part of any solution of the
+/// constraint, see `AllDifferentDomain`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DomainConsistency;
+
+impl<V> AllDifferentConsistency<V> for DomainConsistency
+where
+    V: Variable + 'static,
+    V::Value: Hash + Eq,
+{
+    type Propagator = AllDifferentDomain<V>;
+
+    fn propagator(&self, var_ids: Vec<VarId>) -> AllDifferentDomain<V> {
+        AllDifferentDomain::new(var_ids)
+    }
+}
+
+/// Generalized arc consistent all-different, after Régin.
+///
+/// The variables and values form a bipartite graph with an edge for every
+/// value in a domain. A value can be taken by its variable exactly when the
+/// edge belongs to some matching covering all variables. Given one such
+/// matching, those edges are the matched ones, the ones inside a strongly
+/// connected component of the graph with matched edges pointing from
+/// variable to value and the others from value to variable, and the ones
+/// reachable from a value no variable is matched to.
+#[derive(Clone, Debug)]
+pub struct AllDifferentDomain<V>
+where
+    V: Variable,
+{
+    id: PropId,
+    var_ids: Vec<VarId>,
+    /// The value matched to each variable by the last propagation, as a hint
+    /// for the next, see `graph::hinted`.
+    matching: Vec<Option<V::Value>>,
+}
+
+impl<V> AllDifferentDomain<V>
+where
+    V: Variable,
+{
+    pub fn new<I, Q>(var_ids: I) -> AllDifferentDomain<V>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+    {
+        let mut seen = HashSet::new();
+        let var_ids = var_ids
+            .into_iter()
+            .map(|id| *id.borrow())
+            .filter(|&id| seen.insert(id))
+            .collect::<Vec<_>>();
+        AllDifferentDomain {
+            id: PropId { id: 0 },
+            matching: vec![None; var_ids.len()],
+            var_ids,
+        }
+    }
+}
+
+/// The variable-value graph of an `AllDifferentDomain`. Variables are the
+/// nodes `0..n`, values are numbered from `n` on.
+struct ValueGraph {
+    vars: usize,
+    /// The values of each variable's domain.
+    edges: Vec<Vec<usize>>,
+    var_match: Vec<Option<usize>>,
+    value_match: Vec<Option<usize>>,
+}
+
+impl ValueGraph {
+    /// Tries to match `var` by an augmenting path, using Kuhn's algorithm.
+    fn augment(&mut self, var: usize, visited: &mut [bool]) -> bool {
+        for i in 0..self.edges[var].len() {
+            let value = self.edges[var][i];
+            if visited[value] {
+                continue;
+            }
+            visited[value] = true;
+            let free = match self.value_match[value] {
+                None => true,
+                Some(other) => self.augment(other, visited),
+            };
+            if free {
+                self.var_match[var] = Some(value);
+                self.value_match[value] = Some(var);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The nodes an edge leaves `node` for: a variable points to its matched
+    /// value, a value to the variables it is not matched to.
+    fn successors(&self, node: usize, value_vars: &[Vec<usize>]) -> Vec<usize> {
+        if node < self.vars {
+            self.var_match[node].map(|value| self.vars + value).into_iter().collect()
+        } else {
+            let value = node - self.vars;
+            value_vars[value]
+                .iter()
+                .cloned()
+                .filter(|&var| self.value_match[value] != Some(var))
+                .collect()
+        }
+    }
+
+    /// Numbers the strongly connected components.
+    fn components(&self, value_vars: &[Vec<usize>]) -> Vec<usize> {
+        let successors = (0..self.vars + value_vars.len())
+            .map(|node| self.successors(node, value_vars))
+            .collect::<Vec<_>>();
+        components(&successors)
+    }
+
+    /// Marks the nodes reachable from values no variable is matched to.
+    fn reachable_from_free(&self, value_vars: &[Vec<usize>]) -> Vec<bool> {
+        let mut reached = vec![false; self.vars + value_vars.len()];
+        let mut stack = vec![];
+        for value in 0..value_vars.len() {
+            if self.value_match[value].is_none() {
+                reached[self.vars + value] = true;
+                stack.push(self.vars + value);
+            }
+        }
+        while let Some(node) = stack.pop() {
+            for next in self.successors(node, value_vars) {
+                if !reached[next] {
+                    reached[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        reached
+    }
+}
+
+impl<V> AllDifferentDomain<V>
+where
+    V: Variable,
+    V::Value: Hash + Eq,
+{
+    fn filter(&mut self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut values = vec![];
+        let mut value_index = HashMap::new();
+        let mut edges = vec![];
+        for &var_id in &self.var_ids {
+            let mut var_edges = vec![];
+            for value in vars.var(var_id).possibilities() {
+                let next = values.len();
+                let index = *value_index.entry(value.clone()).or_insert(next);
+                if index == next {
+                    values.push(value);
+                }
+                var_edges.push(index);
+            }
+            edges.push(var_edges);
+        }
+        let mut value_vars = vec![vec![]; values.len()];
+        for (var, var_edges) in edges.iter().enumerate() {
+            for &value in var_edges {
+                value_vars[value].push(var);
+            }
+        }
+
+        let mut graph = ValueGraph {
+            vars: self.var_ids.len(),
+            edges,
+            var_match: vec![None; self.var_ids.len()],
+            value_match: vec![None; values.len()],
+        };
+        for (var, value) in hinted(&self.matching, &value_index, &graph.edges) {
+            if graph.value_match[value].is_none() {
+                graph.var_match[var] = Some(value);
+                graph.value_match[value] = Some(var);
+            }
+        }
+        for var in 0..graph.vars {
+            if graph.var_match[var].is_none() {
+                let mut visited = vec![false; values.len()];
+                if !graph.augment(var, &mut visited) {
+                    return Err(Failure::reason("variables outnumber their values"));
+                }
+            }
+        }
+        for (var, value) in graph.var_match.iter().enumerate() {
+            self.matching[var] = value.map(|value| values[value].clone());
+        }
+
+        let component = graph.components(&value_vars);
+        let reached = graph.reachable_from_free(&value_vars);
+        let mut domain_updates = BTreeSet::new();
+        for var in 0..graph.vars {
+            for &value in &graph.edges[var] {
+                let node = graph.vars + value;
+                let consistent = graph.var_match[var] == Some(value)
+                    || component[var] == component[node]
+                    || reached[node];
+                if !consistent {
+                    let var_id = self.var_ids[var];
+                    domain_updates.insert(vars.remove(var_id, &values[value])?);
+                }
+            }
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for AllDifferentDomain<V>
+where
+    V: Variable + 'static,
+    V::Value: Hash + Eq,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        for &var_id in &self.var_ids {
+            vars.subscribe(var_id, self.id);
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+}
+
+/// Narrows the bounds of the variables so that each bound can be part of a
+/// solution when domains are treated as intervals, see `AllDifferentBounds`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BoundsConsistency;
+
+impl<V> AllDifferentConsistency<V> for BoundsConsistency
+where
+    V: OrderedVariable + 'static,
+    V::Value: Integer,
+{
+    type Propagator = AllDifferentBounds<V>;
+
+    fn propagator(&self, var_ids: Vec<VarId>) -> AllDifferentBounds<V> {
+        AllDifferentBounds::new(var_ids)
+    }
+}
+
+/// Bounds consistent all-different, using the Hall interval algorithm of
+/// López-Ortiz, Quimper, Tromp and van Beek.
+///
+/// Only the bounds of the variables are read and changed, so it runs in
+/// `O(n log n)` regardless of the size of the domains. A Hall interval is a
+/// range of values holding as many values as there are variables whose
+/// domain lies inside it; no other variable can take those values, and when
+/// more variables than values fall inside a range the constraint fails.
+#[derive(Clone, Debug)]
+pub struct AllDifferentBounds<V> {
+    id: PropId,
+    var_ids: Vec<VarId>,
+    phantom: PhantomData<V>,
+}
+
+impl<V> AllDifferentBounds<V> {
+    pub fn new<I, Q>(var_ids: I) -> AllDifferentBounds<V>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+    {
+        let mut seen = HashSet::new();
+        let var_ids = var_ids
+            .into_iter()
+            .map(|id| *id.borrow())
+            .filter(|&id| seen.insert(id))
+            .collect();
+        AllDifferentBounds {
+            id: PropId { id: 0 },
+            var_ids,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// The domain of a variable as the half-open range `min..max`, with the
+/// positions of both ends among the sorted distinct bounds. The ends are
+/// `i128` so that `max`, and the sentinels around the bounds, stay exact for
+/// domains reaching the limits of `i64`.
+#[derive(Clone, Copy, Debug)]
+struct Interval {
+    min: i128,
+    max: i128,
+    min_rank: usize,
+    max_rank: usize,
+}
+
+fn path_set(t: &mut [usize], start: usize, end: usize, to: usize) {
+    let mut l = start;
+    while l != end {
+        let k = l;
+        l = t[k];
+        t[k] = to;
+    }
+}
+
+fn path_min(t: &[usize], mut i: usize) -> usize {
+    while t[i] < i {
+        i = t[i];
+    }
+    i
+}
+
+fn path_max(t: &[usize], mut i: usize) -> usize {
+    while t[i] > i {
+        i = t[i];
+    }
+    i
+}
+
+/// The Hall interval sweeps over the ranks of the bounds of `intervals`.
+struct HallIntervals {
+    intervals: Vec<Interval>,
+    /// Indices into `intervals`, sorted by `min` and by `max`.
+    min_sorted: Vec<usize>,
+    max_sorted: Vec<usize>,
+    /// The distinct bounds in increasing order, with a sentinel at each end.
+    bounds: Vec<i128>,
+    /// The index of the last real bound in `bounds`.
+    last: usize,
+}
+
+impl HallIntervals {
+    fn new(intervals: Vec<Interval>) -> HallIntervals {
+        let n = intervals.len();
+        let mut min_sorted: Vec<usize> = (0..n).collect();
+        let mut max_sorted: Vec<usize> = (0..n).collect();
+        min_sorted.sort_by_key(|&i| intervals[i].min);
+        max_sorted.sort_by_key(|&i| intervals[i].max);
+        let mut hall = HallIntervals {
+            intervals,
+            min_sorted,
+            max_sorted,
+            bounds: vec![],
+            last: 0,
+        };
+        hall.rank();
+        hall
+    }
+
+    /// Merges the sorted bounds into `bounds` and records the rank of every
+    /// interval's ends.
+    fn rank(&mut self) {
+        let n = self.intervals.len();
+        let mut min = self.intervals[self.min_sorted[0]].min;
+        let mut max = self.intervals[self.max_sorted[0]].max;
+        let mut last = min - 2;
+        let mut nb = 0;
+        self.bounds = vec![last];
+        let (mut i, mut j) = (0, 0);
+        loop {
+            if i < n && min <= max {
+                if min != last {
+                    last = min;
+                    self.bounds.push(last);
+                    nb += 1;
+                }
+                self.intervals[self.min_sorted[i]].min_rank = nb;
+                i += 1;
+                if i < n {
+                    min = self.intervals[self.min_sorted[i]].min;
+                }
+            } else {
+                if max != last {
+                    last = max;
+                    self.bounds.push(last);
+                    nb += 1;
+                }
+                self.intervals[self.max_sorted[j]].max_rank = nb;
+                j += 1;
+                if j == n {
+                    break;
+                }
+                max = self.intervals[self.max_sorted[j]].max;
+            }
+        }
+        self.last = nb;
+        self.bounds.push(last + 2);
+    }
+
+    /// Raises the lower bounds above the Hall intervals below them.
+    fn filter_lower(&mut self) -> Result<(), Failure> {
+        let nb = self.last;
+        let bounds = &self.bounds;
+        let mut t = vec![0; nb + 2];
+        let mut h = vec![0; nb + 2];
+        let mut d = vec![0; nb + 2];
+        for i in 1..nb + 2 {
+            t[i] = i - 1;
+            h[i] = i - 1;
+            d[i] = bounds[i] - bounds[i - 1];
+        }
+        for &index in &self.max_sorted {
+            let interval = &mut self.intervals[index];
+            let x = interval.min_rank;
+            let y = interval.max_rank;
+            let mut z = path_max(&t, x + 1);
+            let j = t[z];
+            d[z] -= 1;
+            if d[z] == 0 {
+                t[z] = z + 1;
+                z = path_max(&t, t[z]);
+                t[z] = j;
+            }
+            path_set(&mut t, x + 1, z, z);
+            if d[z] < bounds[z] - bounds[y] {
+                return Err(Failure::reason("more variables than values in an interval"));
+            }
+            if h[x] > x {
+                let w = path_max(&h, h[x]);
+                interval.min = bounds[w];
+                path_set(&mut h, x, w, w);
+            }
+            if d[z] == bounds[z] - bounds[y] {
+                let hy = h[y];
+                path_set(&mut h, hy, j - 1, y);
+                h[y] = j - 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowers the upper bounds below the Hall intervals above them.
+    fn filter_upper(&mut self) -> Result<(), Failure> {
+        let nb = self.last;
+        let bounds = &self.bounds;
+        let mut t = vec![0; nb + 2];
+        let mut h = vec![0; nb + 2];
+        let mut d = vec![0; nb + 2];
+        for i in 0..nb + 1 {
+            t[i] = i + 1;
+            h[i] = i + 1;
+            d[i] = bounds[i + 1] - bounds[i];
+        }
+        for &index in self.min_sorted.iter().rev() {
+            let interval = &mut self.intervals[index];
+            let x = interval.max_rank;
+            let y = interval.min_rank;
+            let mut z = path_min(&t, x - 1);
+            let j = t[z];
+            d[z] -= 1;
+            if d[z] == 0 {
+                t[z] = z - 1;
+                z = path_min(&t, t[z]);
+                t[z] = j;
+            }
+            path_set(&mut t, x - 1, z, z);
+            if d[z] < bounds[y] - bounds[z] {
+                return Err(Failure::reason("more variables than values in an interval"));
+            }
+            if h[x] < x {
+                let w = path_min(&h, h[x]);
+                interval.max = bounds[w];
+                path_set(&mut h, x, w, w);
+            }
+            if d[z] == bounds[y] - bounds[z] {
+                let hy = h[y];
+                path_set(&mut h, hy, j + 1, y);
+                h[y] = j + 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The new bound `bound` of `var_id`, which wipes the domain out if the
+/// variable's type cannot hold it.
+fn narrow<T>(var_id: VarId, bound: i128) -> Result<T, Failure>
+where
+    T: Integer,
+{
+    if bound < i64::MIN as i128 || bound > i64::MAX as i128 {
+        return Err(Failure::wipe_out(var_id));
+    }
+    convert(bound as i64).ok_or_else(|| Failure::wipe_out(var_id))
+}
+
+impl<V> AllDifferentBounds<V>
+where
+    V: OrderedVariable,
+    V::Value: Integer,
+{
+    fn filter(&self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut domain_updates = BTreeSet::new();
+        if self.var_ids.is_empty() {
+            return Ok(domain_updates);
+        }
+        let intervals = self.var_ids
+            .iter()
+            .map(|&var_id| {
+                let var = vars.var(var_id);
+                Interval {
+                    min: var.min().to_i64() as i128,
+                    max: var.max().to_i64() as i128 + 1,
+                    min_rank: 0,
+                    max_rank: 0,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut hall = HallIntervals::new(intervals.clone());
+        hall.filter_lower()?;
+        hall.filter_upper()?;
+
+        for (i, &var_id) in self.var_ids.iter().enumerate() {
+            let (old, new) = (intervals[i], hall.intervals[i]);
+            if new.min > old.min {
+                let bound = narrow(var_id, new.min)?;
+                domain_updates.insert(vars.remove_below(var_id, &bound)?);
+            }
+            if new.max < old.max {
+                let bound = narrow(var_id, new.max - 1)?;
+                domain_updates.insert(vars.remove_above(var_id, &bound)?);
+            }
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for AllDifferentBounds<V>
+where
+    V: OrderedVariable + 'static,
+    V::Value: Integer,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        for &var_id in &self.var_ids {
+            vars.subscribe(var_id, self.id);
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+}
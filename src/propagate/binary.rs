@@ -0,0 +1,160 @@
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+
+use var::{VarSet, VarId, DomainUpdate, OrderedVariable, Integer, Failure};
+use propagate::{Propagate, PropId, Relation, convert};
+
+/// Propagator for `x <relation> y + offset`.
+///
+/// `<`, `>` and `>=` are rewritten to `<=` when the propagator is created,
+/// swapping the variables where needed. `<=` filters the bounds, `!=`
+/// removes a value once the other variable is fixed, and `==` keeps only the
+/// values with a counterpart in the other domain, which takes time
+/// proportional to the domain sizes.
+#[derive(Clone, Debug)]
+pub struct Binary<V> {
+    id: PropId,
+    x: VarId,
+    relation: Relation,
+    y: VarId,
+    offset: i64,
+    phantom: PhantomData<V>,
+}
+
+impl<V> Binary<V> {
+    pub fn new(x: VarId, relation: Relation, y: VarId, offset: i64) -> Binary<V> {
+        let (x, relation, y, offset) = match relation {
+            Relation::Lt => (x, Relation::Le, y, offset - 1),
+            Relation::Ge => (y, Relation::Le, x, -offset),
+            Relation::Gt => (y, Relation::Le, x, -offset - 1),
+            _ => (x, relation, y, offset),
+        };
+        Binary {
+            id: PropId { id: 0 },
+            x,
+            relation,
+            y,
+            offset,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<V> Binary<V>
+where
+    V: OrderedVariable,
+    V::Value: Integer,
+{
+    /// Filters `x <= y + offset` on the bounds of both variables.
+    fn filter_le(
+        &self,
+        vars: &mut VarSet<V>,
+        domain_updates: &mut BTreeSet<DomainUpdate>,
+    ) -> Result<(), Failure> {
+        let bound = vars.var(self.y).max().to_i64() + self.offset;
+        if bound < vars.var(self.x).min().to_i64() {
+            return Err(Failure::wipe_out(self.x));
+        }
+        if let Some(bound) = convert::<V::Value>(bound) {
+            domain_updates.insert(vars.remove_above(self.x, &bound)?);
+        }
+
+        let bound = vars.var(self.x).min().to_i64() - self.offset;
+        if bound > vars.var(self.y).max().to_i64() {
+            return Err(Failure::wipe_out(self.y));
+        }
+        if let Some(bound) = convert::<V::Value>(bound) {
+            domain_updates.insert(vars.remove_below(self.y, &bound)?);
+        }
+        Ok(())
+    }
+
+    /// Removes from `to` the value `from` is fixed to, shifted by `shift`.
+    fn filter_ne(
+        vars: &mut VarSet<V>,
+        from: VarId,
+        to: VarId,
+        shift: i64,
+        domain_updates: &mut BTreeSet<DomainUpdate>,
+    ) -> Result<(), Failure> {
+        let value = vars.var(from).value().map(|value| value.to_i64() + shift);
+        if let Some(value) = value.and_then(convert::<V::Value>) {
+            domain_updates.insert(vars.remove(to, &value)?);
+        }
+        Ok(())
+    }
+
+    /// Removes the values of `to` that have no value of `from` equal to them
+    /// once shifted by `shift`.
+    fn filter_eq(
+        vars: &mut VarSet<V>,
+        from: VarId,
+        to: VarId,
+        shift: i64,
+        domain_updates: &mut BTreeSet<DomainUpdate>,
+    ) -> Result<(), Failure> {
+        let unsupported = vars.var(to)
+            .possibilities()
+            .filter(|value| match convert::<V::Value>(value.to_i64() - shift) {
+                Some(support) => !vars.var(from).contains(&support),
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        for value in unsupported {
+            domain_updates.insert(vars.remove(to, &value)?);
+        }
+        Ok(())
+    }
+
+    fn filter(&self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut domain_updates = BTreeSet::new();
+        match self.relation {
+            Relation::Le => self.filter_le(vars, &mut domain_updates)?,
+            Relation::Eq => {
+                Binary::filter_eq(vars, self.y, self.x, self.offset, &mut domain_updates)?;
+                Binary::filter_eq(vars, self.x, self.y, -self.offset, &mut domain_updates)?;
+            }
+            Relation::Ne => {
+                Binary::filter_ne(vars, self.y, self.x, self.offset, &mut domain_updates)?;
+                Binary::filter_ne(vars, self.x, self.y, -self.offset, &mut domain_updates)?;
+            }
+            _ => unreachable!(),
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for Binary<V>
+where
+    V: OrderedVariable + 'static,
+    V::Value: Integer,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        vars.subscribe(self.x, self.id);
+        if self.y != self.x {
+            vars.subscribe(self.y, self.id);
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+}
@@ -0,0 +1,325 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashSet};
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+use var::{VarSet, VarId, DomainUpdate, OrderedVariable, Integer, Failure};
+use propagate::{Propagate, PropId, convert};
+use propagate::linear::div_ceil;
+
+/// A task of a scheduling constraint. It starts at the value of `start`,
+/// runs for `duration` and uses `demand` units of the resource meanwhile.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Task {
+    pub start: VarId,
+    pub duration: i64,
+    pub demand: i64,
+}
+
+impl Task {
+    /// A task using one unit of the resource.
+    pub fn new(start: VarId, duration: i64) -> Task {
+        Task {
+            start,
+            duration,
+            demand: 1,
+        }
+    }
+
+    pub fn with_demand(mut self, demand: i64) -> Task {
+        self.demand = demand;
+        self
+    }
+}
+
+/// The time window of a task under the current domains: it starts no
+/// earlier than `est` and ends no later than `lct`.
+///
+/// Times and energies saturate at the limits of `i64` rather than overflow,
+/// so a task whose start is near `i64::MAX` is taken to end by `i64::MAX`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Window {
+    pub est: i64,
+    pub lct: i64,
+    pub duration: i64,
+    pub demand: i64,
+}
+
+impl Window {
+    pub fn new<V>(vars: &VarSet<V>, task: &Task) -> Window
+    where
+        V: OrderedVariable,
+        V::Value: Integer,
+    {
+        let start = vars.var(task.start);
+        Window {
+            est: start.min().to_i64(),
+            lct: start.max().to_i64().saturating_add(task.duration),
+            duration: task.duration,
+            demand: task.demand,
+        }
+    }
+
+    pub fn lst(&self) -> i64 {
+        self.lct.saturating_sub(self.duration)
+    }
+
+    pub fn ect(&self) -> i64 {
+        self.est.saturating_add(self.duration)
+    }
+
+    pub fn energy(&self) -> i64 {
+        self.duration.saturating_mul(self.demand)
+    }
+
+    /// The window of the task when time runs backwards. Times are mirrored
+    /// to `!t`, which is `-t - 1` but unlike negation cannot overflow, and
+    /// times found on mirrored windows are mapped back the same way.
+    pub fn mirror(&self) -> Window {
+        Window {
+            est: !self.lct,
+            lct: !self.est,
+            ..*self
+        }
+    }
+}
+
+/// Narrows the start of `task` to `est..=lst`, failing if that is empty.
+pub(crate) fn restrict_start<V>(
+    vars: &mut VarSet<V>,
+    task: &Task,
+    est: i64,
+    lst: i64,
+    domain_updates: &mut BTreeSet<DomainUpdate>,
+) -> Result<(), Failure>
+where
+    V: OrderedVariable,
+    V::Value: Integer,
+{
+    let start = vars.var(task.start);
+    if est > start.max().to_i64() || lst < start.min().to_i64() || est > lst {
+        return Err(Failure::wipe_out(task.start));
+    }
+    if let Some(est) = convert::<V::Value>(est) {
+        domain_updates.insert(vars.remove_below(task.start, &est)?);
+    }
+    if let Some(lst) = convert::<V::Value>(lst) {
+        domain_updates.insert(vars.remove_above(task.start, &lst)?);
+    }
+    Ok(())
+}
+
+/// Cumulative resource constraint: at any time, the tasks that are running
+/// use at most `capacity` units of the resource.
+///
+/// Two rules filter the start times, each applied forwards and, on mirrored
+/// windows, backwards. Time-tabling builds the profile of the compulsory
+/// parts, the times a task runs wherever it is placed, and moves each task
+/// out of the stretches where it would exceed the capacity. Edge-finding
+/// compares the energy of the tasks inside a window to what the resource can
+/// supply there: it fails on overloads, and when a task cannot fit into a
+/// window together with a set of tasks, it must end after all of them and
+/// starts no earlier than the energy of the set allows. Edge-finding only
+/// considers the sets of tasks within windows bounded by an earliest start
+/// and a latest end, and runs in `O(n^3)` for `n` tasks.
+#[derive(Clone, Debug)]
+pub struct Cumulative<V> {
+    id: PropId,
+    tasks: Vec<Task>,
+    capacity: i64,
+    phantom: PhantomData<V>,
+}
+
+impl<V> Cumulative<V> {
+    /// # Panics
+    ///
+    /// Panics if the capacity or a duration or demand is negative.
+    pub fn new<I, Q>(tasks: I, capacity: i64) -> Cumulative<V>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<Task>,
+    {
+        assert!(capacity >= 0, "capacity is negative");
+        let tasks = tasks
+            .into_iter()
+            .map(|task| *task.borrow())
+            .inspect(|task| {
+                assert!(task.duration >= 0, "task duration is negative");
+                assert!(task.demand >= 0, "task demand is negative");
+            })
+            // Tasks that use nothing cannot conflict with anything.
+            .filter(|task| task.duration > 0 && task.demand > 0)
+            .collect();
+        Cumulative {
+            id: PropId { id: 0 },
+            tasks,
+            capacity,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// The profile of the compulsory parts of `windows` as `(from, to, height)`
+/// stretches in increasing order, failing where it exceeds `capacity`.
+fn profile(windows: &[Window], capacity: i64) -> Result<Vec<(i64, i64, i64)>, Failure> {
+    let mut events = vec![];
+    for window in windows {
+        if window.lst() < window.ect() {
+            events.push((window.lst(), window.demand));
+            events.push((window.ect(), -window.demand));
+        }
+    }
+    events.sort();
+    let mut stretches = vec![];
+    let mut height: i64 = 0;
+    for (i, &(time, change)) in events.iter().enumerate() {
+        height = height.saturating_add(change);
+        let next = events.get(i + 1).map_or(time, |&(next, _)| next);
+        if next > time && height > 0 {
+            if height > capacity {
+                return Err(Failure::reason("compulsory parts exceed the capacity"));
+            }
+            stretches.push((time, next, height));
+        }
+    }
+    Ok(stretches)
+}
+
+/// The earliest starts allowed by the compulsory parts of the other tasks.
+fn time_table(windows: &[Window], capacity: i64) -> Result<Vec<i64>, Failure> {
+    let stretches = profile(windows, capacity)?;
+    Ok(windows
+        .iter()
+        .map(|window| {
+            let mut est = window.est;
+            for &(from, to, height) in &stretches {
+                let own = if window.lst() <= from && to <= window.ect() {
+                    window.demand
+                } else {
+                    0
+                };
+                let overlaps = est < to && est.saturating_add(window.duration) > from;
+                if overlaps && (height - own).saturating_add(window.demand) > capacity {
+                    est = to;
+                }
+            }
+            est
+        })
+        .collect())
+}
+
+/// The energy a resource of `capacity` supplies from `start` to `end`.
+fn supply(capacity: i64, start: i64, end: i64) -> i64 {
+    capacity.saturating_mul(end.saturating_sub(start))
+}
+
+/// The earliest starts found by edge-finding.
+fn edge_find(windows: &[Window], capacity: i64) -> Result<Vec<i64>, Failure> {
+    let mut earliest = windows.iter().map(|window| window.est).collect::<Vec<_>>();
+    let mut ends = windows.iter().map(|window| window.lct).collect::<Vec<_>>();
+    ends.sort();
+    ends.dedup();
+    for &end in &ends {
+        // The tasks ending by `end`, latest start first, so that each prefix
+        // is the set of tasks within a window `est..end`.
+        let mut inside = (0..windows.len())
+            .filter(|&j| windows[j].lct <= end)
+            .collect::<Vec<_>>();
+        inside.sort_by_key(|&j| Reverse(windows[j].est));
+
+        let mut energy: i64 = 0;
+        for &j in &inside {
+            energy = energy.saturating_add(windows[j].energy());
+            if energy > supply(capacity, windows[j].est, end) {
+                return Err(Failure::reason("tasks overload the resource in a window"));
+            }
+        }
+
+        for (i, window) in windows.iter().enumerate() {
+            let mut energy: i64 = 0;
+            let mut bound = i64::MIN;
+            for &j in inside.iter().filter(|&&j| j != i) {
+                let start = windows[j].est;
+                energy = energy.saturating_add(windows[j].energy());
+                // The energy that cannot run next to the task within the
+                // window, which delays its start.
+                let rest = energy.saturating_sub(supply(capacity - window.demand, start, end));
+                if rest > 0 {
+                    bound = bound.max(start.saturating_add(div_ceil(rest, window.demand)));
+                }
+                let needed = energy.saturating_add(window.energy());
+                if needed > supply(capacity, start.min(window.est), end) {
+                    earliest[i] = earliest[i].max(bound);
+                }
+            }
+        }
+    }
+    Ok(earliest)
+}
+
+impl<V> Cumulative<V>
+where
+    V: OrderedVariable,
+    V::Value: Integer,
+{
+    fn filter(&self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        if self.tasks.iter().any(|task| task.demand > self.capacity) {
+            return Err(Failure::reason("a task demands more than the capacity"));
+        }
+        let windows = self.tasks
+            .iter()
+            .map(|task| Window::new(vars, task))
+            .collect::<Vec<_>>();
+        let mirrored = windows.iter().map(Window::mirror).collect::<Vec<_>>();
+        let forward = time_table(&windows, self.capacity)?;
+        let backward = time_table(&mirrored, self.capacity)?;
+        let forward_edges = edge_find(&windows, self.capacity)?;
+        let backward_edges = edge_find(&mirrored, self.capacity)?;
+
+        let mut domain_updates = BTreeSet::new();
+        for (i, task) in self.tasks.iter().enumerate() {
+            let est = forward[i].max(forward_edges[i]);
+            let lct = !backward[i].max(backward_edges[i]);
+            let lst = lct.saturating_sub(task.duration);
+            restrict_start(vars, task, est, lst, &mut domain_updates)?;
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for Cumulative<V>
+where
+    V: OrderedVariable + 'static,
+    V::Value: Integer,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut seen = HashSet::new();
+        for task in &self.tasks {
+            if seen.insert(task.start) {
+                vars.subscribe(task.start, self.id);
+            }
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::{BTreeSet, HashSet};
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+use var::{VarSet, DomainUpdate, OrderedVariable, Integer, Failure};
+use propagate::{Propagate, PropId, Task};
+use propagate::cumulative::{Window, restrict_start};
+
+/// Unary resource constraint: no two tasks run at the same time. The
+/// demands of the tasks are ignored.
+///
+/// The filtering follows Vilím, using a Θ-tree that gives the earliest
+/// completion time of a set of tasks in `O(log n)` per insertion or removal,
+/// so each rule runs in `O(n log n)`. Overload checking fails when the tasks
+/// ending by some time cannot all complete by then. Detectable precedences
+/// start a task after the tasks that must precede it because they cannot
+/// start after it ends. Not-last ends a task before the latest start of the
+/// others when it cannot run after all of them. Each rule is also applied to
+/// mirrored windows, which gives the opposite bounds and, for not-last, the
+/// not-first rule.
+#[derive(Clone, Debug)]
+pub struct Disjunctive<V> {
+    id: PropId,
+    tasks: Vec<Task>,
+    phantom: PhantomData<V>,
+}
+
+impl<V> Disjunctive<V> {
+    /// # Panics
+    ///
+    /// Panics if a duration is negative.
+    pub fn new<I, Q>(tasks: I) -> Disjunctive<V>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<Task>,
+    {
+        let tasks = tasks
+            .into_iter()
+            .map(|task| *task.borrow())
+            .inspect(|task| assert!(task.duration >= 0, "task duration is negative"))
+            // Tasks without duration never overlap anything.
+            .filter(|task| task.duration > 0)
+            .collect();
+        Disjunctive {
+            id: PropId { id: 0 },
+            tasks,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// A balanced binary tree over the tasks in order of earliest start, giving
+/// the earliest completion time of the tasks inserted into it.
+struct ThetaTree {
+    /// The position of each task's leaf.
+    position: Vec<usize>,
+    /// The total duration of the tasks below each node.
+    duration: Vec<i64>,
+    /// The earliest completion time of the tasks below each node.
+    ect: Vec<i64>,
+}
+
+impl ThetaTree {
+    fn new(windows: &[Window]) -> ThetaTree {
+        let leaves = windows.len().next_power_of_two();
+        let mut order = (0..windows.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| windows[i].est);
+        let mut position = vec![0; windows.len()];
+        for (rank, &i) in order.iter().enumerate() {
+            position[i] = leaves + rank;
+        }
+        ThetaTree {
+            position,
+            duration: vec![0; 2 * leaves],
+            ect: vec![i64::MIN; 2 * leaves],
+        }
+    }
+
+    fn contains(&self, task: usize) -> bool {
+        self.ect[self.position[task]] != i64::MIN
+    }
+
+    fn insert(&mut self, windows: &[Window], task: usize) {
+        let node = self.position[task];
+        self.duration[node] = windows[task].duration;
+        self.ect[node] = windows[task].ect();
+        self.update(node / 2);
+    }
+
+    fn remove(&mut self, task: usize) {
+        let node = self.position[task];
+        self.duration[node] = 0;
+        self.ect[node] = i64::MIN;
+        self.update(node / 2);
+    }
+
+    fn update(&mut self, mut node: usize) {
+        while node > 0 {
+            let (left, right) = (2 * node, 2 * node + 1);
+            self.duration[node] = self.duration[left] + self.duration[right];
+            let through_left = self.ect[left].saturating_add(self.duration[right]);
+            self.ect[node] = self.ect[right].max(through_left);
+            node /= 2;
+        }
+    }
+
+    /// The earliest completion time of the inserted tasks.
+    fn ect(&self) -> i64 {
+        self.ect[1]
+    }
+
+    /// The earliest completion time of the inserted tasks other than `task`.
+    fn ect_without(&mut self, windows: &[Window], task: usize) -> i64 {
+        if !self.contains(task) {
+            return self.ect();
+        }
+        self.remove(task);
+        let ect = self.ect();
+        self.insert(windows, task);
+        ect
+    }
+}
+
+fn sorted_by<F>(windows: &[Window], key: F) -> Vec<usize>
+where
+    F: Fn(&Window) -> i64,
+{
+    let mut order = (0..windows.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| key(&windows[i]));
+    order
+}
+
+fn overload_check(windows: &[Window]) -> Result<(), Failure> {
+    let mut tree = ThetaTree::new(windows);
+    for i in sorted_by(windows, |window| window.lct) {
+        tree.insert(windows, i);
+        if tree.ect() > windows[i].lct {
+            return Err(Failure::reason("tasks cannot all run before their latest end"));
+        }
+    }
+    Ok(())
+}
+
+/// The earliest starts after the tasks each task is detected to follow.
+fn detectable_precedences(windows: &[Window]) -> Vec<i64> {
+    let mut earliest = windows.iter().map(|window| window.est).collect::<Vec<_>>();
+    let by_lst = sorted_by(windows, Window::lst);
+    let mut next = 0;
+    let mut tree = ThetaTree::new(windows);
+    for i in sorted_by(windows, Window::ect) {
+        // Tasks that must start before `i` ends precede it.
+        while next < by_lst.len() && windows[i].ect() > windows[by_lst[next]].lst() {
+            tree.insert(windows, by_lst[next]);
+            next += 1;
+        }
+        earliest[i] = earliest[i].max(tree.ect_without(windows, i));
+    }
+    earliest
+}
+
+/// The latest completions before the last start of the tasks that each task
+/// cannot follow.
+fn not_last(windows: &[Window]) -> Vec<i64> {
+    let mut latest = windows.iter().map(|window| window.lct).collect::<Vec<_>>();
+    let by_lst = sorted_by(windows, Window::lst);
+    let mut next = 0;
+    let mut tree = ThetaTree::new(windows);
+    for i in sorted_by(windows, |window| window.lct) {
+        while next < by_lst.len() && windows[i].lct > windows[by_lst[next]].lst() {
+            tree.insert(windows, by_lst[next]);
+            next += 1;
+        }
+        if tree.ect_without(windows, i) > windows[i].lst() {
+            // The latest start among the inserted tasks other than `i`.
+            let last = by_lst[..next].iter().rev().find(|&&j| j != i).unwrap();
+            latest[i] = latest[i].min(windows[*last].lst());
+        }
+    }
+    latest
+}
+
+impl<V> Disjunctive<V>
+where
+    V: OrderedVariable,
+    V::Value: Integer,
+{
+    fn filter(&self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let windows = self.tasks
+            .iter()
+            .map(|task| Window::new(vars, task))
+            .collect::<Vec<_>>();
+        let mirrored = windows.iter().map(Window::mirror).collect::<Vec<_>>();
+        overload_check(&windows)?;
+        let forward = detectable_precedences(&windows);
+        let backward = detectable_precedences(&mirrored);
+        let before_last = not_last(&windows);
+        let after_first = not_last(&mirrored);
+
+        let mut domain_updates = BTreeSet::new();
+        for (i, task) in self.tasks.iter().enumerate() {
+            let est = forward[i].max(!after_first[i]);
+            let lct = before_last[i].min(!backward[i]);
+            let lst = lct.saturating_sub(task.duration);
+            restrict_start(vars, task, est, lst, &mut domain_updates)?;
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for Disjunctive<V>
+where
+    V: OrderedVariable + 'static,
+    V::Value: Integer,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut seen = HashSet::new();
+        for task in &self.tasks {
+            if seen.insert(task.start) {
+                vars.subscribe(task.start, self.id);
+            }
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+}
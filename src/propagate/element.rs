@@ -0,0 +1,162 @@
+use std::collections::{BTreeSet, HashSet};
+
+use var::{VarSet, VarId, DomainUpdate, Variable, Integer, Failure};
+use propagate::{Propagate, PropId};
+
+/// An entry of the array of an `Element` constraint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ElementEntry<T> {
+    Value(T),
+    Var(VarId),
+}
+
+/// Propagator for `result = array[index]`, where the entries of `array` are
+/// constants or variables.
+///
+/// Filtering is domain consistent in every direction: an index is kept while
+/// its entry and `result` share a value, a value of `result` while some
+/// remaining index can produce it, and once the index is fixed the chosen
+/// entry is narrowed to the domain of `result`.
+#[derive(Clone, Debug)]
+pub struct Element<V>
+where
+    V: Variable,
+{
+    id: PropId,
+    index: VarId,
+    array: Vec<ElementEntry<V::Value>>,
+    result: VarId,
+}
+
+impl<V> Element<V>
+where
+    V: Variable,
+{
+    pub fn new(index: VarId, array: Vec<ElementEntry<V::Value>>, result: VarId) -> Element<V> {
+        Element {
+            id: PropId { id: 0 },
+            index,
+            array,
+            result,
+        }
+    }
+}
+
+impl<V> Element<V>
+where
+    V: Variable,
+    V::Value: Integer,
+{
+    /// The position `value` of the index stands for, if it is one.
+    fn position(&self, value: &V::Value) -> Option<usize> {
+        let position = value.to_i64();
+        if position >= 0 && (position as u64) < self.array.len() as u64 {
+            Some(position as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the entry at `position` can take a value `result` can take.
+    fn supported(&self, vars: &VarSet<V>, position: usize) -> bool {
+        let result = vars.var(self.result);
+        match self.array[position] {
+            ElementEntry::Value(ref value) => result.contains(value),
+            ElementEntry::Var(var_id) => {
+                let entry = vars.var(var_id);
+                if entry.size() <= result.size() {
+                    entry.possibilities().any(|value| result.contains(&value))
+                } else {
+                    result.possibilities().any(|value| entry.contains(&value))
+                }
+            }
+        }
+    }
+
+    fn filter(&self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut domain_updates = BTreeSet::new();
+
+        let unsupported = vars.var(self.index)
+            .possibilities()
+            .filter(|value| match self.position(value) {
+                Some(position) => !self.supported(vars, position),
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        for value in unsupported {
+            domain_updates.insert(vars.remove(self.index, &value)?);
+        }
+
+        let mut reachable = HashSet::new();
+        for value in vars.var(self.index).possibilities() {
+            match self.array[self.position(&value).unwrap()] {
+                ElementEntry::Value(value) => {
+                    reachable.insert(value);
+                }
+                ElementEntry::Var(var_id) => reachable.extend(vars.var(var_id).possibilities()),
+            }
+        }
+        let unreachable = vars.var(self.result)
+            .possibilities()
+            .filter(|value| !reachable.contains(value))
+            .collect::<Vec<_>>();
+        for value in unreachable {
+            domain_updates.insert(vars.remove(self.result, &value)?);
+        }
+
+        let chosen = vars.var(self.index).value().map(|value| self.position(value).unwrap());
+        if let Some(ElementEntry::Var(var_id)) = chosen.map(|position| self.array[position]) {
+            let outside = vars.var(var_id)
+                .possibilities()
+                .filter(|value| !vars.var(self.result).contains(value))
+                .collect::<Vec<_>>();
+            for value in outside {
+                domain_updates.insert(vars.remove(var_id, &value)?);
+            }
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for Element<V>
+where
+    V: Variable + 'static,
+    V::Value: Integer,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut var_ids = vec![self.index, self.result];
+        for entry in &self.array {
+            if let ElementEntry::Var(var_id) = *entry {
+                var_ids.push(var_id);
+            }
+        }
+        let mut seen = HashSet::new();
+        for var_id in var_ids {
+            if seen.insert(var_id) {
+                vars.subscribe(var_id, self.id);
+            }
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+}
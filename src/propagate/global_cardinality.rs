@@ -0,0 +1,350 @@
+use std::collections::{HashMap, BTreeSet, HashSet};
+use std::borrow::Borrow;
+
+use var::{VarSet, VarId, DomainUpdate, Variable, Integer, Failure};
+use propagate::{Propagate, PropId};
+use propagate::graph::{hinted, components};
+
+/// How often a value of a `GlobalCardinality` constraint must occur.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Occurrences {
+    /// Between the two counts, inclusive.
+    Between(usize, usize),
+    /// As often as the variable says.
+    Var(VarId),
+}
+
+impl Occurrences {
+    pub fn exactly(count: usize) -> Occurrences {
+        Occurrences::Between(count, count)
+    }
+}
+
+/// Global cardinality constraint, filtered with flows after Régin.
+///
+/// An assignment of the variables is a flow from a source through the values
+/// to the variables, where each value carries between its least and most
+/// occurrences. Given one feasible flow, a variable can take a value exactly
+/// when the edge between them carries flow or lies in a strongly connected
+/// component of the residual graph, which makes the filtering of the
+/// variables generalized arc consistent. Count variables are narrowed to
+/// between the number of variables fixed to their value and the number that
+/// can still take it, and their bounds limit the flow. Values that are not
+/// listed may occur any number of times.
+#[derive(Clone, Debug)]
+pub struct GlobalCardinality<V>
+where
+    V: Variable,
+{
+    id: PropId,
+    var_ids: Vec<VarId>,
+    occurrences: Vec<(V::Value, Occurrences)>,
+    /// The value each variable took in the last flow, as a hint for the
+    /// next, see `graph::hinted`.
+    assignment: Vec<Option<V::Value>>,
+}
+
+impl<V> GlobalCardinality<V>
+where
+    V: Variable,
+    V::Value: Integer,
+{
+    /// # Panics
+    ///
+    /// Panics if a value is listed more than once, or if its least count is
+    /// above its greatest.
+    pub fn new<I, Q, J>(var_ids: I, occurrences: J) -> GlobalCardinality<V>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+        J: IntoIterator<Item = (V::Value, Occurrences)>,
+    {
+        let var_ids = var_ids.into_iter().map(|id| *id.borrow()).collect::<Vec<_>>();
+        let occurrences = occurrences.into_iter().collect::<Vec<_>>();
+        let mut seen = HashSet::new();
+        for &(value, occurrences) in &occurrences {
+            assert!(seen.insert(value), "value {:?} is listed more than once", value);
+            if let Occurrences::Between(min, max) = occurrences {
+                assert!(min <= max, "occurrences of {:?} are empty", value);
+            }
+        }
+        GlobalCardinality {
+            id: PropId { id: 0 },
+            assignment: vec![None; var_ids.len()],
+            var_ids,
+            occurrences,
+        }
+    }
+}
+
+/// The value network of a `GlobalCardinality`. Variables are the nodes
+/// `0..n`, values are numbered from `n` on and the source comes last.
+struct Network {
+    vars: usize,
+    /// The values of each variable's domain.
+    edges: Vec<Vec<usize>>,
+    /// The variables that can take each value.
+    value_vars: Vec<Vec<usize>>,
+    lower: Vec<usize>,
+    upper: Vec<usize>,
+    assigned: Vec<Option<usize>>,
+    flow: Vec<usize>,
+}
+
+impl Network {
+    fn assign(&mut self, var: usize, value: usize) {
+        if let Some(old) = self.assigned[var] {
+            self.flow[old] -= 1;
+        }
+        self.assigned[var] = Some(value);
+        self.flow[value] += 1;
+    }
+
+    /// Tries to assign `var` by an augmenting path that keeps every value
+    /// within its greatest count.
+    fn augment(&mut self, var: usize, visited: &mut [bool]) -> bool {
+        for i in 0..self.edges[var].len() {
+            let value = self.edges[var][i];
+            if visited[value] {
+                continue;
+            }
+            visited[value] = true;
+            if self.flow[value] < self.upper[value] {
+                self.assign(var, value);
+                return true;
+            }
+            let holders = self.value_vars[value]
+                .iter()
+                .cloned()
+                .filter(|&other| self.assigned[other] == Some(value))
+                .collect::<Vec<_>>();
+            for other in holders {
+                if self.augment(other, visited) {
+                    self.assign(var, value);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Moves one more variable to `start` along a chain of reassignments
+    /// ending at a value that is above its least count.
+    fn raise(&mut self, start: usize) -> bool {
+        // For each value reached, the variable that would leave it and the
+        // value that variable would move to.
+        let mut previous = vec![None; self.flow.len()];
+        let mut visited = vec![false; self.flow.len()];
+        visited[start] = true;
+        let mut queue = vec![start];
+        let mut head = 0;
+        while head < queue.len() {
+            let value = queue[head];
+            head += 1;
+            for &var in &self.value_vars[value] {
+                let from = self.assigned[var].unwrap();
+                if visited[from] {
+                    continue;
+                }
+                visited[from] = true;
+                previous[from] = Some((var, value));
+                if self.flow[from] > self.lower[from] {
+                    let mut current = from;
+                    while let Some((var, to)) = previous[current] {
+                        self.assign(var, to);
+                        current = to;
+                    }
+                    return true;
+                }
+                queue.push(from);
+            }
+        }
+        false
+    }
+
+    /// The residual graph of the flow: a variable points to its value, a
+    /// value to the other variables that can take it and to the source if
+    /// its flow can shrink, and the source to the values whose flow can grow.
+    fn residual(&self) -> Vec<Vec<usize>> {
+        let values = self.flow.len();
+        let source = self.vars + values;
+        let mut graph = vec![vec![]; source + 1];
+        for (successors, value) in graph.iter_mut().zip(&self.assigned) {
+            successors.push(self.vars + value.unwrap());
+        }
+        for value in 0..values {
+            let node = self.vars + value;
+            for &var in &self.value_vars[value] {
+                if self.assigned[var] != Some(value) {
+                    graph[node].push(var);
+                }
+            }
+            if self.flow[value] > self.lower[value] {
+                graph[node].push(source);
+            }
+            if self.flow[value] < self.upper[value] {
+                graph[source].push(node);
+            }
+        }
+        graph
+    }
+}
+
+impl<V> GlobalCardinality<V>
+where
+    V: Variable,
+    V::Value: Integer,
+{
+    fn filter(&mut self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut values = vec![];
+        let mut value_index = HashMap::new();
+        let mut edges = vec![];
+        for &var_id in &self.var_ids {
+            let mut var_edges = vec![];
+            for value in vars.var(var_id).possibilities() {
+                let next = values.len();
+                let index = *value_index.entry(value).or_insert(next);
+                if index == next {
+                    values.push(value);
+                }
+                var_edges.push(index);
+            }
+            edges.push(var_edges);
+        }
+        for &(value, _) in &self.occurrences {
+            let next = values.len();
+            if *value_index.entry(value).or_insert(next) == next {
+                values.push(value);
+            }
+        }
+        let mut value_vars = vec![vec![]; values.len()];
+        for (var, var_edges) in edges.iter().enumerate() {
+            for &value in var_edges {
+                value_vars[value].push(var);
+            }
+        }
+
+        let mut domain_updates = BTreeSet::new();
+        let mut lower = vec![0; values.len()];
+        let mut upper = vec![self.var_ids.len(); values.len()];
+        for &(value, occurrences) in &self.occurrences {
+            let value = value_index[&value];
+            match occurrences {
+                Occurrences::Between(min, max) => {
+                    lower[value] = min;
+                    upper[value] = max;
+                }
+                Occurrences::Var(count_id) => {
+                    let fixed = value_vars[value]
+                        .iter()
+                        .filter(|&&var| vars.var(self.var_ids[var]).size() == 1)
+                        .count() as i64;
+                    let possible = value_vars[value].len() as i64;
+                    let impossible = vars.var(count_id)
+                        .possibilities()
+                        .filter(|count| count.to_i64() < fixed || count.to_i64() > possible)
+                        .collect::<Vec<_>>();
+                    for count in impossible {
+                        domain_updates.insert(vars.remove(count_id, &count)?);
+                    }
+                    let counts = vars.var(count_id)
+                        .possibilities()
+                        .map(|count| count.to_i64() as usize)
+                        .collect::<Vec<_>>();
+                    lower[value] = *counts.iter().min().unwrap();
+                    upper[value] = *counts.iter().max().unwrap();
+                }
+            }
+        }
+
+        let vars_len = self.var_ids.len();
+        let mut network = Network {
+            vars: vars_len,
+            edges,
+            value_vars,
+            lower,
+            upper,
+            assigned: vec![None; vars_len],
+            flow: vec![0; values.len()],
+        };
+        for (var, value) in hinted(&self.assignment, &value_index, &network.edges) {
+            if network.flow[value] < network.upper[value] {
+                network.assign(var, value);
+            }
+        }
+        for var in 0..vars_len {
+            if network.assigned[var].is_none() {
+                let mut visited = vec![false; values.len()];
+                if !network.augment(var, &mut visited) {
+                    return Err(Failure::reason("values cannot take all the variables"));
+                }
+            }
+        }
+        for value in 0..values.len() {
+            while network.flow[value] < network.lower[value] {
+                if !network.raise(value) {
+                    return Err(Failure::reason("too few variables for the occurrences"));
+                }
+            }
+        }
+        for (var, value) in network.assigned.iter().enumerate() {
+            self.assignment[var] = value.map(|value| values[value]);
+        }
+
+        let component = components(&network.residual());
+        for var in 0..vars_len {
+            for &value in &network.edges[var] {
+                let consistent = network.assigned[var] == Some(value)
+                    || component[var] == component[vars_len + value];
+                if !consistent {
+                    let var_id = self.var_ids[var];
+                    domain_updates.insert(vars.remove(var_id, &values[value])?);
+                }
+            }
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for GlobalCardinality<V>
+where
+    V: Variable + 'static,
+    V::Value: Integer,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut var_ids = self.var_ids.clone();
+        for &(_, occurrences) in &self.occurrences {
+            if let Occurrences::Var(count_id) = occurrences {
+                var_ids.push(count_id);
+            }
+        }
+        let mut seen = HashSet::new();
+        for var_id in var_ids {
+            if seen.insert(var_id) {
+                vars.subscribe(var_id, self.id);
+            }
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+}
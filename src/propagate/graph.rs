@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The `(var, value)` pairs of a hint that are still edges of the graph,
+/// with values numbered by `value_index` and `edges` the values of each
+/// variable's domain.
+///
+/// The matching-based propagators keep the values their variables took in
+/// the last propagation and start the next matching or flow from them. The
+/// hint is not trailed, so after backtracking it may describe a node that is
+/// no longer current: it is only a starting point, and each pair is checked
+/// against the current domains before use.
+pub(crate) fn hinted<T>(
+    hint: &[Option<T>],
+    value_index: &HashMap<T, usize>,
+    edges: &[Vec<usize>],
+) -> Vec<(usize, usize)>
+where
+    T: Hash + Eq,
+{
+    hint.iter()
+        .enumerate()
+        .filter_map(|(var, old)| {
+            old.as_ref()
+                .and_then(|value| value_index.get(value))
+                .map(|&value| (var, value))
+        })
+        .filter(|&(var, value)| edges[var].contains(&value))
+        .collect()
+}
+
+/// Numbers the strongly connected components of `graph`, given as the
+/// successors of each node, with Tarjan's algorithm.
+pub(crate) fn components(graph: &[Vec<usize>]) -> Vec<usize> {
+    let nodes = graph.len();
+    let mut tarjan = Tarjan {
+        index: vec![None; nodes],
+        low: vec![0; nodes],
+        on_stack: vec![false; nodes],
+        stack: vec![],
+        component: vec![0; nodes],
+        next_index: 0,
+        next_component: 0,
+    };
+    for node in 0..nodes {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(graph, node);
+        }
+    }
+    tarjan.component
+}
+
+struct Tarjan {
+    index: Vec<Option<usize>>,
+    low: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    component: Vec<usize>,
+    next_index: usize,
+    next_component: usize,
+}
+
+impl Tarjan {
+    /// Visits the nodes reachable from `root`. Rather than recursing, the
+    /// depth-first search keeps its path in `calls`, each node with the
+    /// position of the next successor to look at, so long paths through
+    /// large graphs cannot overflow the stack.
+    fn visit(&mut self, graph: &[Vec<usize>], root: usize) {
+        self.enter(root);
+        let mut calls = vec![(root, 0)];
+        while let Some((node, edge)) = calls.pop() {
+            if let Some(&next) = graph[node].get(edge) {
+                calls.push((node, edge + 1));
+                match self.index[next] {
+                    None => {
+                        self.enter(next);
+                        calls.push((next, 0));
+                    }
+                    Some(index) if self.on_stack[next] => {
+                        self.low[node] = self.low[node].min(index);
+                    }
+                    Some(_) => {}
+                }
+            } else {
+                self.leave(node);
+                if let Some(&(parent, _)) = calls.last() {
+                    self.low[parent] = self.low[parent].min(self.low[node]);
+                }
+            }
+        }
+    }
+
+    fn enter(&mut self, node: usize) {
+        self.index[node] = Some(self.next_index);
+        self.low[node] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+    }
+
+    /// Closes the component rooted at `node`, once all its successors have
+    /// been visited, if it is the root of one.
+    fn leave(&mut self, node: usize) {
+        if Some(self.low[node]) == self.index[node] {
+            loop {
+                let member = self.stack.pop().unwrap();
+                self.on_stack[member] = false;
+                self.component[member] = self.next_component;
+                if member == node {
+                    break;
+                }
+            }
+            self.next_component += 1;
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeSet;
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+use var::{VarSet, VarId, DomainUpdate, OrderedVariable, Integer, Failure};
+use propagate::{Propagate, PropId, Relation};
+
+/// Integer division rounding towards negative infinity.
+pub fn div_floor(a: i64, b: i64) -> i64 {
+    let q = a / b;
+    if (a % b != 0) && ((a < 0) != (b < 0)) {
+        q - 1
+    } else {
+        q
+    }
+}
+
+/// Integer division rounding towards positive infinity.
+pub fn div_ceil(a: i64, b: i64) -> i64 {
+    -div_floor(-a, b)
+}
+
+/// The constraint `sum(a_i * x_i) <relation> c` over integer variables.
+///
+/// Strict and `>=` relations are rewritten to `<=` when the propagator is
+/// created, so only `<=`, `==` and `!=` are handled during search. The two
+/// inequalities filter the bounds of every term; `!=` waits until all but one
+/// variable is fixed and then removes the single forbidden value.
+#[derive(Clone, Debug)]
+pub struct Linear<V> {
+    id: PropId,
+    terms: Vec<(i64, VarId)>,
+    relation: Relation,
+    constant: i64,
+    phantom: PhantomData<V>,
+}
+
+impl<V> Linear<V> {
+    pub fn new<I, Q>(terms: I, relation: Relation, constant: i64) -> Linear<V>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<(i64, VarId)>,
+    {
+        let mut terms = terms
+            .into_iter()
+            .map(|t| *t.borrow())
+            .filter(|&(a, _)| a != 0)
+            .collect::<Vec<_>>();
+        let (relation, constant) = match relation {
+            Relation::Lt => (Relation::Le, constant - 1),
+            Relation::Gt => (Relation::Ge, constant + 1),
+            _ => (relation, constant),
+        };
+        let (relation, constant) = if relation == Relation::Ge {
+            for term in &mut terms {
+                term.0 = -term.0;
+            }
+            (Relation::Le, -constant)
+        } else {
+            (relation, constant)
+        };
+        Linear {
+            id: PropId { id: 0 },
+            terms,
+            relation,
+            constant,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<V> Linear<V>
+where
+    V: OrderedVariable,
+    V::Value: Integer,
+{
+    fn min_term(vars: &VarSet<V>, a: i64, var_id: VarId) -> i64 {
+        let var = vars.var(var_id);
+        if a > 0 {
+            a * var.min().to_i64()
+        } else {
+            a * var.max().to_i64()
+        }
+    }
+
+    /// Filters the bounds for `sum(sign * a_i * x_i) <= sign * c`.
+    fn filter_le(
+        &self,
+        vars: &mut VarSet<V>,
+        sign: i64,
+        domain_updates: &mut BTreeSet<DomainUpdate>,
+    ) -> Result<(), Failure> {
+        let constant = sign * self.constant;
+        let min_sum: i64 = self.terms
+            .iter()
+            .map(|&(a, var_id)| Linear::min_term(vars, sign * a, var_id))
+            .sum();
+        if min_sum > constant {
+            return Err(Failure::reason("minimum of the sum exceeds the bound"));
+        }
+
+        for &(a, var_id) in &self.terms {
+            let a = sign * a;
+            let slack = constant - (min_sum - Linear::min_term(vars, a, var_id));
+            let update = if a > 0 {
+                let bound = V::Value::from_i64(div_floor(slack, a));
+                vars.remove_above(var_id, &bound)?
+            } else {
+                let bound = V::Value::from_i64(div_ceil(slack, a));
+                vars.remove_below(var_id, &bound)?
+            };
+            domain_updates.insert(update);
+        }
+        Ok(())
+    }
+
+    fn filter_ne(
+        &self,
+        vars: &mut VarSet<V>,
+        domain_updates: &mut BTreeSet<DomainUpdate>,
+    ) -> Result<(), Failure> {
+        let mut sum = 0;
+        let mut unfixed = None;
+        for &(a, var_id) in &self.terms {
+            match vars.var(var_id).value() {
+                Some(value) => sum += a * value.to_i64(),
+                None if unfixed.is_none() => unfixed = Some((a, var_id)),
+                None => return Ok(()),
+            }
+        }
+
+        match unfixed {
+            None if sum == self.constant => Err(Failure::reason("sum equals the excluded value")),
+            None => Ok(()),
+            Some((a, var_id)) => {
+                let rest = self.constant - sum;
+                if rest % a == 0 {
+                    let value = V::Value::from_i64(rest / a);
+                    if value.to_i64() == rest / a {
+                        domain_updates.insert(vars.remove(var_id, &value)?);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn filter(&self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut domain_updates = BTreeSet::new();
+        match self.relation {
+            Relation::Le => self.filter_le(vars, 1, &mut domain_updates)?,
+            Relation::Eq => {
+                self.filter_le(vars, 1, &mut domain_updates)?;
+                self.filter_le(vars, -1, &mut domain_updates)?;
+            }
+            Relation::Ne => self.filter_ne(vars, &mut domain_updates)?,
+            _ => unreachable!(),
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for Linear<V>
+where
+    V: OrderedVariable + 'static,
+    V::Value: Integer,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        for &(_, var_id) in &self.terms {
+            vars.subscribe(var_id, self.id);
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+}
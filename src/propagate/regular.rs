@@ -0,0 +1,219 @@
+use std::collections::{HashMap, BTreeSet, HashSet};
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use var::{VarSet, VarId, DomainUpdate, Variable, Failure};
+use propagate::{Propagate, PropId};
+
+/// A deterministic finite automaton over the values of a variable type.
+///
+/// States are numbered from 0. A value without a transition from a state
+/// leads nowhere, so words using it there are rejected.
+#[derive(Clone, Debug)]
+pub struct Automaton<T>
+where
+    T: Hash + Eq,
+{
+    start: usize,
+    transitions: Vec<HashMap<T, usize>>,
+    accepting: Vec<bool>,
+}
+
+impl<T> Automaton<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Creates an automaton with `states` states, starting in `start`, that
+    /// moves along `transitions` given as `(from, value, to)` triples and
+    /// accepts a word when it ends in one of the `accepting` states.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a state is out of range, or if two transitions leave the
+    /// same state on the same value.
+    pub fn new<I, Q, J, R>(
+        states: usize,
+        start: usize,
+        transitions: I,
+        accepting: J,
+    ) -> Automaton<T>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<(usize, T, usize)>,
+        J: IntoIterator<Item = R>,
+        R: Borrow<usize>,
+    {
+        assert!(start < states, "start state out of range");
+        let mut automaton = Automaton {
+            start,
+            transitions: vec![HashMap::new(); states],
+            accepting: vec![false; states],
+        };
+        for transition in transitions {
+            let (from, ref value, to) = *transition.borrow();
+            assert!(from < states && to < states, "transition state out of range");
+            let previous = automaton.transitions[from].insert(value.clone(), to);
+            assert!(previous.is_none(), "automaton is not deterministic");
+        }
+        for state in accepting {
+            let state = *state.borrow();
+            assert!(state < states, "accepting state out of range");
+            automaton.accepting[state] = true;
+        }
+        automaton
+    }
+
+    pub fn states(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// Whether the automaton accepts `word`.
+    pub fn accepts<I, Q>(&self, word: I) -> bool
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<T>,
+    {
+        let mut state = self.start;
+        for value in word {
+            match self.transitions[state].get(value.borrow()) {
+                Some(&next) => state = next,
+                None => return false,
+            }
+        }
+        self.accepting[state]
+    }
+}
+
+/// Constrains a sequence of variables to spell a word accepted by an
+/// automaton.
+///
+/// The propagator unfolds the automaton into a layered graph, with one layer
+/// of states per position and an edge for each transition on a value still
+/// in the domain of the variable at that position. A forward pass finds the
+/// states reachable from the start and a backward pass those that still lead
+/// to an accepting state. A value is kept if it labels an edge between two
+/// states found by both passes, which makes the propagation generalized arc
+/// consistent. The graph is rebuilt on every call, in time proportional to
+/// the number of variables times the number of transitions.
+#[derive(Clone, Debug)]
+pub struct Regular<V>
+where
+    V: Variable,
+    V::Value: Hash + Eq,
+{
+    id: PropId,
+    var_ids: Vec<VarId>,
+    automaton: Arc<Automaton<V::Value>>,
+}
+
+impl<V> Regular<V>
+where
+    V: Variable,
+    V::Value: Hash + Eq,
+{
+    pub fn new<I, Q>(var_ids: I, automaton: Automaton<V::Value>) -> Regular<V>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+    {
+        Regular {
+            id: PropId { id: 0 },
+            var_ids: var_ids.into_iter().map(|id| *id.borrow()).collect(),
+            automaton: Arc::new(automaton),
+        }
+    }
+
+    fn filter(&self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let automaton = &self.automaton;
+        let states = automaton.states();
+        let len = self.var_ids.len();
+
+        let mut layers = vec![vec![false; states]; len + 1];
+        layers[0][automaton.start] = true;
+        for (i, &var_id) in self.var_ids.iter().enumerate() {
+            let var = vars.var(var_id);
+            for state in 0..states {
+                if !layers[i][state] {
+                    continue;
+                }
+                for (value, &next) in &automaton.transitions[state] {
+                    if var.contains(value) {
+                        layers[i + 1][next] = true;
+                    }
+                }
+            }
+        }
+
+        for (reached, &accepting) in layers[len].iter_mut().zip(&automaton.accepting) {
+            *reached = *reached && accepting;
+        }
+        if !layers[len].contains(&true) {
+            return Err(Failure::reason("no accepted word is left"));
+        }
+
+        let mut domain_updates = BTreeSet::new();
+        for i in (0..len).rev() {
+            let var_id = self.var_ids[i];
+            let mut supported = HashSet::new();
+            for state in 0..states {
+                if !layers[i][state] {
+                    continue;
+                }
+                let mut useful = false;
+                for (value, &next) in &automaton.transitions[state] {
+                    if layers[i + 1][next] && vars.var(var_id).contains(value) {
+                        supported.insert(value);
+                        useful = true;
+                    }
+                }
+                layers[i][state] = useful;
+            }
+            let unsupported = vars.var(var_id)
+                .possibilities()
+                .filter(|value| !supported.contains(value))
+                .collect::<Vec<_>>();
+            for value in unsupported {
+                domain_updates.insert(vars.remove(var_id, &value)?);
+            }
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for Regular<V>
+where
+    V: Variable + 'static,
+    V::Value: Hash + Eq,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        let mut seen = HashSet::new();
+        for &var_id in &self.var_ids {
+            if seen.insert(var_id) {
+                vars.subscribe(var_id, self.id);
+            }
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::{HashMap, BTreeSet, HashSet};
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use var::{VarSet, VarId, DomainUpdate, Variable, Failure};
+use propagate::{Propagate, PropId};
+
+/// Whether the tuples of a `Table` are the allowed or the forbidden
+/// assignments of its variables.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TableKind {
+    Allowed,
+    Forbidden,
+}
+
+/// Extensional constraint given by a list of tuples, using compact tables.
+///
+/// The tuples whose values are all still in the domains are kept in a bitset.
+/// For each variable and value a precomputed mask tells which tuples hold
+/// that value, so after a domain change the bitset is narrowed to the union
+/// of the masks of the remaining values. With allowed tuples a value is kept
+/// as long as its mask meets the bitset, which makes the propagation
+/// generalized arc consistent. With forbidden tuples a value is removed once
+/// every assignment of the other variables together with it is forbidden.
+#[derive(Clone, Debug)]
+pub struct Table<V>
+where
+    V: Variable,
+{
+    id: PropId,
+    var_ids: Vec<VarId>,
+    kind: TableKind,
+    /// For each variable, the mask of the tuples holding each value. Shared
+    /// between copies, as only `current` and `sizes` change during search.
+    supports: Arc<Vec<HashMap<V::Value, Vec<u64>>>>,
+    /// The tuples that are still valid.
+    current: Vec<u64>,
+    /// The domain sizes when the propagator last ran, to skip the variables
+    /// that have not changed since.
+    sizes: Vec<usize>,
+}
+
+impl<V> Table<V>
+where
+    V: Variable,
+    V::Value: Hash + Eq,
+{
+    /// # Panics
+    ///
+    /// Panics if a tuple does not have one value per variable.
+    pub fn new<I, Q, J, T>(var_ids: I, tuples: J, kind: TableKind) -> Table<V>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+        J: IntoIterator<Item = T>,
+        T: AsRef<[V::Value]>,
+    {
+        let var_ids = var_ids.into_iter().map(|id| *id.borrow()).collect::<Vec<_>>();
+        // Duplicates would be counted twice when looking for forbidden values.
+        let mut seen = HashSet::new();
+        let tuples = tuples
+            .into_iter()
+            .map(|tuple| tuple.as_ref().to_vec())
+            .filter(|tuple| seen.insert(tuple.clone()))
+            .collect::<Vec<_>>();
+        let words = tuples.len().div_ceil(64);
+
+        let mut supports = vec![HashMap::new(); var_ids.len()];
+        for (t, tuple) in tuples.iter().enumerate() {
+            assert_eq!(tuple.len(), var_ids.len(), "tuple length differs from the arity");
+            for (var_supports, value) in supports.iter_mut().zip(tuple) {
+                let mask = var_supports
+                    .entry(value.clone())
+                    .or_insert_with(|| vec![0; words]);
+                mask[t / 64] |= 1 << (t % 64);
+            }
+        }
+        let mut current = vec![!0; words];
+        if tuples.len() % 64 != 0 {
+            current[words - 1] = (1 << (tuples.len() % 64)) - 1;
+        }
+
+        Table {
+            id: PropId { id: 0 },
+            sizes: vec![usize::MAX; var_ids.len()],
+            var_ids,
+            kind,
+            supports: Arc::new(supports),
+            current,
+        }
+    }
+}
+
+fn intersects(a: &[u64], b: &[u64]) -> bool {
+    a.iter().zip(b).any(|(x, y)| x & y != 0)
+}
+
+fn count_common(a: &[u64], b: &[u64]) -> u64 {
+    a.iter().zip(b).map(|(x, y)| u64::from((x & y).count_ones())).sum()
+}
+
+impl<V> Table<V>
+where
+    V: Variable,
+    V::Value: Hash + Eq,
+{
+    /// Narrows `current` to the tuples whose value for variable `i` is still
+    /// in its domain.
+    fn update_table(&mut self, vars: &VarSet<V>, i: usize) {
+        let mut mask = vec![0; self.current.len()];
+        for value in vars.var(self.var_ids[i]).possibilities() {
+            if let Some(support) = self.supports[i].get(&value) {
+                for (word, bits) in mask.iter_mut().zip(support) {
+                    *word |= bits;
+                }
+            }
+        }
+        for (word, bits) in self.current.iter_mut().zip(&mask) {
+            *word &= bits;
+        }
+        self.sizes[i] = vars.var(self.var_ids[i]).size();
+    }
+
+    fn filter_allowed(
+        &mut self,
+        vars: &mut VarSet<V>,
+        domain_updates: &mut BTreeSet<DomainUpdate>,
+    ) -> Result<(), Failure> {
+        if self.current.iter().all(|&word| word == 0) {
+            return Err(Failure::reason("no allowed tuple is left"));
+        }
+        for i in 0..self.var_ids.len() {
+            let var_id = self.var_ids[i];
+            let unsupported = vars.var(var_id)
+                .possibilities()
+                .filter(|value| match self.supports[i].get(value) {
+                    Some(support) => !intersects(support, &self.current),
+                    None => true,
+                })
+                .collect::<Vec<_>>();
+            if !unsupported.is_empty() {
+                for value in unsupported {
+                    domain_updates.insert(vars.remove(var_id, &value)?);
+                }
+                self.update_table(vars, i);
+            }
+        }
+        Ok(())
+    }
+
+    fn filter_forbidden(
+        &mut self,
+        vars: &mut VarSet<V>,
+        domain_updates: &mut BTreeSet<DomainUpdate>,
+    ) -> Result<(), Failure> {
+        for i in 0..self.var_ids.len() {
+            // The number of assignments of the other variables, capped once
+            // it exceeds the number of tuples that could forbid them.
+            let tuples = self.current.len() as u64 * 64;
+            let mut others = 1u64;
+            for (j, &var_id) in self.var_ids.iter().enumerate() {
+                if j != i {
+                    others = others.saturating_mul(vars.var(var_id).size() as u64);
+                }
+            }
+            if others > tuples {
+                continue;
+            }
+
+            let var_id = self.var_ids[i];
+            let forbidden = vars.var(var_id)
+                .possibilities()
+                .filter(|value| match self.supports[i].get(value) {
+                    Some(support) => count_common(support, &self.current) == others,
+                    None => false,
+                })
+                .collect::<Vec<_>>();
+            if !forbidden.is_empty() {
+                for value in forbidden {
+                    domain_updates.insert(vars.remove(var_id, &value)?);
+                }
+                self.update_table(vars, i);
+            }
+        }
+        Ok(())
+    }
+
+    fn filter(&mut self, vars: &mut VarSet<V>) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        for i in 0..self.var_ids.len() {
+            if vars.var(self.var_ids[i]).size() != self.sizes[i] {
+                self.update_table(vars, i);
+            }
+        }
+        let mut domain_updates = BTreeSet::new();
+        match self.kind {
+            TableKind::Allowed => self.filter_allowed(vars, &mut domain_updates)?,
+            TableKind::Forbidden => self.filter_forbidden(vars, &mut domain_updates)?,
+        }
+        Ok(domain_updates)
+    }
+}
+
+impl<V> Propagate for Table<V>
+where
+    V: Variable + 'static,
+    V::Value: Hash + Eq,
+{
+    type Variable = V;
+
+    fn propagate(
+        &mut self,
+        vars: &mut VarSet<V>,
+        _update: DomainUpdate,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        self.filter(vars)
+    }
+
+    fn initial_propagation(
+        &mut self,
+        vars: &mut VarSet<V>,
+    ) -> Result<BTreeSet<DomainUpdate>, Failure> {
+        for &var_id in &self.var_ids {
+            vars.subscribe(var_id, self.id);
+        }
+        self.filter(vars)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Propagate<Variable = V>> {
+        Box::new((*self).clone())
+    }
+
+    fn set_id(&mut self, id: PropId) {
+        self.id = id;
+    }
+
+    fn is_stateful(&self) -> bool {
+        true
+    }
+}
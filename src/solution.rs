@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::Zip;
+use std::slice;
+use std::sync::Arc;
+use std::ops::Index;
+
+use var::{VarId, VarSet, Variable};
+
+/// The value of every variable of a model in one solution, as returned by
+/// `Solver`.
+#[derive(Clone)]
+pub struct Solution<V>
+where
+    V: Variable,
+{
+    var_ids: Vec<VarId>,
+    values: Vec<V::Value>,
+    names: Arc<Vec<Option<String>>>,
+}
+
+impl<V> Solution<V>
+where
+    V: Variable,
+{
+    /// Reads the values of a `VarSet`, or `None` if some variable is not
+    /// fixed, as happens when its domain is empty.
+    pub fn from_var_set(var_set: &VarSet<V>) -> Option<Solution<V>> {
+        let var_ids = var_set.var_ids().clone();
+        let values = var_ids
+            .iter()
+            .map(|&var_id| var_set.var(var_id).value().cloned())
+            .collect::<Option<_>>()?;
+        Some(Solution {
+            var_ids,
+            values,
+            names: var_set.names().clone(),
+        })
+    }
+
+    /// The name given to `var_id` when it was created, if any.
+    pub fn name(&self, var_id: VarId) -> Option<&str> {
+        self.names
+            .get(var_id.index())
+            .and_then(|name| name.as_deref())
+    }
+
+    /// The value of the variable called `name`.
+    pub fn by_name(&self, name: &str) -> Option<&V::Value> {
+        self.var_ids
+            .iter()
+            .position(|&var_id| self.name(var_id) == Some(name))
+            .map(|index| &self.values[index])
+    }
+
+    /// The value of `var_id`, or `None` if it is not a variable of the model.
+    pub fn get(&self, var_id: VarId) -> Option<&V::Value> {
+        self.values.get(var_id.index())
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Iterates over the variables and their values in creation order.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            inner: self.var_ids.iter().zip(self.values.iter()),
+        }
+    }
+
+    /// The values of an array of variables, as made by
+    /// `Model::create_var_array`.
+    pub fn array<I, Q>(&self, var_ids: I) -> Vec<V::Value>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+    {
+        var_ids
+            .into_iter()
+            .map(|var_id| self[*var_id.borrow()].clone())
+            .collect()
+    }
+
+    /// The values of a matrix of variables, as made by
+    /// `Model::create_var_matrix`.
+    pub fn matrix<R>(&self, var_ids: &[R]) -> Vec<Vec<V::Value>>
+    where
+        R: AsRef<[VarId]>,
+    {
+        var_ids.iter().map(|row| self.array(row.as_ref())).collect()
+    }
+
+    pub fn to_hash_map(&self) -> HashMap<VarId, V::Value> {
+        self.iter().map(|(var_id, value)| (var_id, value.clone())).collect()
+    }
+}
+
+impl<V> Index<VarId> for Solution<V>
+where
+    V: Variable,
+{
+    type Output = V::Value;
+
+    fn index(&self, var_id: VarId) -> &V::Value {
+        &self.values[var_id.index()]
+    }
+}
+
+/// Shows a variable by its name, or by its id if it has none.
+struct Key<'a>(VarId, Option<&'a str>);
+
+impl<'a> fmt::Debug for Key<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.1 {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{:?}", self.0),
+        }
+    }
+}
+
+impl<V> fmt::Debug for Solution<V>
+where
+    V: Variable,
+    V::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|(var_id, value)| (Key(var_id, self.name(var_id)), value)))
+            .finish()
+    }
+}
+
+/// Prints one `name = value` line per variable.
+impl<V> fmt::Display for Solution<V>
+where
+    V: Variable,
+    V::Value: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (var_id, value) in self {
+            writeln!(f, "{:?} = {}", Key(var_id, self.name(var_id)), value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<V> From<Solution<V>> for HashMap<VarId, V::Value>
+where
+    V: Variable,
+{
+    fn from(solution: Solution<V>) -> HashMap<VarId, V::Value> {
+        solution.var_ids.into_iter().zip(solution.values).collect()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a Solution<V>
+where
+    V: Variable,
+{
+    type Item = (VarId, &'a V::Value);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Iter<'a, V> {
+        self.iter()
+    }
+}
+
+/// Iterator over the `(VarId, &Value)` pairs of a `Solution`.
+pub struct Iter<'a, V>
+where
+    V: Variable + 'a,
+{
+    inner: Zip<slice::Iter<'a, VarId>, slice::Iter<'a, V::Value>>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V>
+where
+    V: Variable,
+{
+    type Item = (VarId, &'a V::Value);
+
+    fn next(&mut self) -> Option<(VarId, &'a V::Value)> {
+        self.inner.next().map(|(&var_id, value)| (var_id, value))
+    }
+}
@@ -1,8 +1,208 @@
-use std::collections::{VecDeque, HashSet};
+use std::collections::{VecDeque, HashMap};
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-use var::{VarId, VarSet, DomainUpdate, Variable};
-use propagate::PropSet;
+use var::{VarId, VarSet, VarResult, DomainUpdate, Variable, OrderedVariable, Failure};
+use propagate::{PropId, PropSet};
+use solution::Solution;
+use heuristic::{VarSelector, FirstFail, ValueSelector, FirstValue, Branching, BinaryBranching};
 
+/// A restriction of a single domain, such as `= v` or `<= v`.
+///
+/// Kept as a function pointer so that operations only ordered variables
+/// support can be put in a `Decision` without the solver knowing about them.
+type DomainOperation<V> = fn(&mut V, &<V as Variable>::Value) -> VarResult<DomainUpdate>;
+
+struct Operation<V>
+where
+    V: Variable,
+{
+    symbol: &'static str,
+    apply: DomainOperation<V>,
+}
+
+impl<V> Clone for Operation<V>
+where
+    V: Variable,
+{
+    fn clone(&self) -> Operation<V> {
+        Operation {
+            symbol: self.symbol,
+            apply: self.apply,
+        }
+    }
+}
+
+/// A branching decision taken at a choice point, such as `x = 3` or
+/// `x <= 3`. Every decision knows its negation, which is explored when the
+/// decision itself leads to no (more) solutions.
+#[derive(Clone)]
+pub struct Decision<V>
+where
+    V: Variable,
+{
+    var_id: VarId,
+    value: V::Value,
+    operation: Operation<V>,
+    negation: Operation<V>,
+}
+
+impl<V> Decision<V>
+where
+    V: Variable,
+{
+    fn new(
+        var_id: VarId,
+        value: V::Value,
+        operation: (&'static str, DomainOperation<V>),
+        negation: (&'static str, DomainOperation<V>),
+    ) -> Decision<V> {
+        Decision {
+            var_id,
+            value,
+            operation: Operation {
+                symbol: operation.0,
+                apply: operation.1,
+            },
+            negation: Operation {
+                symbol: negation.0,
+                apply: negation.1,
+            },
+        }
+    }
+
+    /// The decision `x = value`.
+    pub fn assign(var_id: VarId, value: V::Value) -> Decision<V> {
+        Decision::new(var_id, value, ("=", V::instantiate), ("!=", V::remove))
+    }
+
+    /// The decision `x != value`.
+    pub fn exclude(var_id: VarId, value: V::Value) -> Decision<V> {
+        Decision::assign(var_id, value).negate()
+    }
+
+    pub fn var_id(&self) -> VarId {
+        self.var_id
+    }
+
+    pub fn value(&self) -> &V::Value {
+        &self.value
+    }
+
+    pub fn negate(&self) -> Decision<V> {
+        Decision {
+            var_id: self.var_id,
+            value: self.value.clone(),
+            operation: self.negation.clone(),
+            negation: self.operation.clone(),
+        }
+    }
+
+    pub fn apply(&self, vars: &mut VarSet<V>) -> VarResult<DomainUpdate> {
+        (self.operation.apply)(vars.var_mut(self.var_id), &self.value)
+    }
+}
+
+impl<V> Decision<V>
+where
+    V: OrderedVariable,
+{
+    /// The decision `x <= value`.
+    pub fn at_most(var_id: VarId, value: V::Value) -> Decision<V> {
+        Decision::new(var_id, value, ("<=", V::remove_above), (">", remove_at_most))
+    }
+
+    /// The decision `x > value`.
+    pub fn greater_than(var_id: VarId, value: V::Value) -> Decision<V> {
+        Decision::at_most(var_id, value).negate()
+    }
+}
+
+impl<V> fmt::Debug for Decision<V>
+where
+    V: Variable,
+    V::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} {} {:?}", self.var_id, self.operation.symbol, self.value)
+    }
+}
+
+/// Counters describing the work done by a `Solver` so far.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolverStats {
+    /// The number of decisions applied.
+    pub nodes: u64,
+    /// The number of nodes found inconsistent, counting a failed initial
+    /// propagation as one.
+    pub failures: u64,
+    /// The number of times a node was undone to try another alternative or
+    /// to leave an exhausted choice point.
+    pub backtracks: u64,
+    pub solutions: u64,
+    /// The largest number of decisions applied at once.
+    pub max_depth: usize,
+    /// How many times each propagator was run, including its initial
+    /// propagation.
+    pub propagations: HashMap<PropId, u64>,
+    pub propagation_time: Duration,
+    /// Time spent inside `next` other than in propagation.
+    pub search_time: Duration,
+}
+
+impl SolverStats {
+    /// The total number of propagator runs.
+    pub fn total_propagations(&self) -> u64 {
+        self.propagations.values().sum()
+    }
+}
+
+/// A handle for stopping a search from another thread. Clones share the same
+/// flag, so a clone can be kept by whoever needs to cancel while the solver
+/// is moved to a worker thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Asks every solver holding this token to stop. The solver notices
+    /// before its next node or propagator run.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Returned by `Solver::try_next` when the search was cancelled.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "search was interrupted")
+    }
+}
+
+impl Error for Interrupted {}
+
+/// The reason given when propagation is cut short by a `CancelToken`.
+const INTERRUPTED: &str = "search interrupted";
+
+/// The state of the search at the current node. Domain changes are recorded
+/// on the trails of the variable and propagator sets, so moving back up the
+/// search tree is done with `pop_level` instead of keeping copies of states.
 #[derive(Clone)]
 pub struct SearchState<V>
 where
@@ -10,60 +210,56 @@ where
 {
     pub var_set: VarSet<V>,
     pub prop_set: PropSet<V>,
-    pub instantiated_vars: HashSet<VarId>,
+    pub stats: SolverStats,
+    /// Checked before every propagator run; once cancelled, propagation
+    /// fails straight away.
+    pub cancel_token: CancelToken,
 }
 
 impl<V> SearchState<V>
 where
     V: Variable,
 {
-    pub fn instantiate(
-        self,
-        var_id: VarId,
-        value: &V::Value,
-    ) -> (Option<SearchState<V>>, Option<SearchState<V>>) {
-        let mut instantiated_state = self.clone();
-        let mut removed_state = self;
-
-        let mut instantiated_state_retval = None;
-        let mut removed_state_retval = None;
-
-        if let Ok(update) = removed_state.var_set.var_mut(var_id).remove(value) {
-            match removed_state.propagate(update) {
-                Ok(()) => {
-                    removed_state_retval = Some(removed_state);
-                }
-                Err(()) => {}
-            }
-        }
+    pub fn depth(&self) -> usize {
+        self.var_set.depth()
+    }
 
-        if let Ok(update) = instantiated_state.var_set.var_mut(var_id).instantiate(
-            value,
-        )
-        {
-            match instantiated_state.propagate(update) {
-                Ok(()) => {
-                    instantiated_state.instantiated_vars.insert(var_id);
-                    instantiated_state_retval = Some(instantiated_state);
-                }
-                Err(()) => {}
-            }
-        }
+    pub fn push_level(&mut self) {
+        self.var_set.push_level();
+        self.prop_set.push_level();
+    }
+
+    pub fn pop_level(&mut self) {
+        self.var_set.pop_level();
+        self.prop_set.pop_level();
+    }
 
-        (instantiated_state_retval, removed_state_retval)
+    pub fn apply(&mut self, decision: &Decision<V>) -> Result<(), Failure> {
+        let update = decision.apply(&mut self.var_set)?;
+        self.propagate(update)
     }
 
-    pub fn propagate(&mut self, update: DomainUpdate) -> Result<(), ()> {
+    pub fn propagate(&mut self, update: DomainUpdate) -> Result<(), Failure> {
+        let started = Instant::now();
+        let result = self.run_queue(update);
+        self.stats.propagation_time += started.elapsed();
+        result
+    }
+
+    fn run_queue(&mut self, update: DomainUpdate) -> Result<(), Failure> {
         let mut queue = VecDeque::new();
         queue.push_back(update);
         while !queue.is_empty() {
             let latest_update = queue.pop_front().unwrap();
-            // for prop_id in self.prop_set.prop_ids().clone() {
+            if self.cancel_token.is_cancelled() {
+                return Err(Failure::reason(INTERRUPTED));
+            }
             for prop_id in self.var_set.subscriptions(latest_update.var_id()).clone() {
-                let new_updates = self.prop_set.propagator_mut(prop_id).propagate(
-                    &mut self.var_set,
-                    latest_update,
-                )?;
+                *self.stats.propagations.entry(prop_id).or_insert(0) += 1;
+                let new_updates = self.prop_set
+                    .propagator_mut(prop_id)
+                    .propagate(&mut self.var_set, latest_update)
+                    .map_err(|failure| failure.with_prop(prop_id))?;
                 for update in new_updates {
                     match update {
                         DomainUpdate::Unchanged(_) => {}
@@ -77,29 +273,33 @@ where
         Ok(())
     }
 
-    pub fn choose_var(&self) -> Option<VarId> {
-        self.var_set
-            .var_ids()
-            .iter()
-            .filter(|id| !self.instantiated_vars.contains(id))
-            .min_by_key(|id| self.var_set.var(**id).size())
-            .cloned()
-    }
-
-    pub fn choose_value(&self, var_id: VarId) -> Option<&V::Value> {
-        self.var_set.var(var_id).possibilities().nth(0)
-    }
-
-    pub fn initial_propagation(&mut self) -> Result<(), ()> {
+    pub fn initial_propagation(&mut self) -> Result<(), Failure> {
+        // Domains only become empty by failing, unless they were created
+        // empty, and the selectors take an empty domain for a fixed one.
+        let var_set = &self.var_set;
+        if let Some(&var_id) = var_set.var_ids().iter().find(|&&id| var_set.var(id).size() == 0) {
+            return Err(Failure::wipe_out(var_id));
+        }
+        let started = Instant::now();
         let mut domain_updates = vec![];
         for &prop_id in &self.prop_set.prop_ids().clone() {
+            if self.cancel_token.is_cancelled() {
+                self.stats.propagation_time += started.elapsed();
+                return Err(Failure::reason(INTERRUPTED));
+            }
+            *self.stats.propagations.entry(prop_id).or_insert(0) += 1;
             let updates = self.prop_set.propagator_mut(prop_id).initial_propagation(
                 &mut self.var_set,
-            )?;
-            for update in updates {
-                domain_updates.push(update);
+            );
+            match updates {
+                Ok(updates) => domain_updates.extend(updates),
+                Err(failure) => {
+                    self.stats.propagation_time += started.elapsed();
+                    return Err(failure.with_prop(prop_id));
+                }
             }
         }
+        self.stats.propagation_time += started.elapsed();
         for update in domain_updates {
             self.propagate(update)?;
         }
@@ -107,13 +307,98 @@ where
     }
 }
 
+/// The alternatives of a choice point that have not been tried yet, stored
+/// in reverse order. `active` is set while one of its alternatives has a
+/// level pushed on the trail.
+#[derive(Clone)]
+struct ChoicePoint<V>
+where
+    V: Variable,
+{
+    alternatives: Vec<Decision<V>>,
+    active: bool,
+}
+
+/// The variable being optimised and the best value found for it so far.
+/// `tighten` removes every value that is not strictly better than `bound`.
+#[derive(Clone)]
+struct Objective<V>
+where
+    V: Variable,
+{
+    var_id: VarId,
+    bound: Option<V::Value>,
+    tighten: DomainOperation<V>,
+}
+
+fn remove_at_least<V>(var: &mut V, bound: &V::Value) -> VarResult<DomainUpdate>
+where
+    V: OrderedVariable,
+{
+    let max = var.max().clone();
+    var.remove_range(bound, &max)
+}
+
+fn remove_at_most<V>(var: &mut V, bound: &V::Value) -> VarResult<DomainUpdate>
+where
+    V: OrderedVariable,
+{
+    let min = var.min().clone();
+    var.remove_range(&min, bound)
+}
+
+/// Budgets that stop the search early. Every limit is off by default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SearchLimits {
+    /// The number of decisions the search may apply.
+    pub max_nodes: Option<u64>,
+    /// The number of decisions that may fail, either directly or during the
+    /// propagation following them.
+    pub max_failures: Option<u64>,
+    pub max_solutions: Option<u64>,
+    /// Wall-clock time measured from the first call to `next`.
+    pub time_limit: Option<Duration>,
+}
+
+/// The limit that stopped a search.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Limit {
+    Nodes,
+    Failures,
+    Solutions,
+    Time,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SearchStatus {
+    /// The search may still find more solutions.
+    Running,
+    /// The whole search tree has been explored, so there are no more
+    /// solutions. If none were found the model is infeasible.
+    Exhausted,
+    /// The search was stopped by a limit before exploring the whole tree.
+    LimitReached(Limit),
+    /// The search was stopped through its `CancelToken`.
+    Interrupted,
+}
+
 #[derive(Clone)]
 pub struct Solver<V>
 where
     V: Variable,
 {
-    state_stack: Vec<SearchState<V>>,
+    state: SearchState<V>,
+    choice_points: Vec<ChoicePoint<V>>,
+    objective: Option<Objective<V>>,
+    var_selector: Box<dyn VarSelector<V>>,
+    value_selectors: Vec<Box<dyn ValueSelector<V>>>,
+    value_selector_of: HashMap<VarId, usize>,
+    branching: Box<dyn Branching<V>>,
+    limits: SearchLimits,
+    started: Option<Instant>,
     initialized: bool,
+    status: SearchStatus,
+    last_failure: Option<Failure>,
 }
 
 impl<V> Solver<V>
@@ -122,49 +407,373 @@ where
 {
     pub fn new(var_set: VarSet<V>, prop_set: PropSet<V>) -> Solver<V> {
         let state = SearchState {
-            var_set: var_set,
-            prop_set: prop_set,
-            instantiated_vars: HashSet::new(),
+            var_set,
+            prop_set,
+            stats: SolverStats::default(),
+            cancel_token: CancelToken::new(),
         };
         Solver {
-            state_stack: vec![state],
+            state,
+            choice_points: vec![],
+            objective: None,
+            var_selector: Box::new(FirstFail),
+            value_selectors: vec![Box::new(FirstValue)],
+            value_selector_of: HashMap::new(),
+            branching: Box::new(BinaryBranching),
+            limits: SearchLimits::default(),
+            started: None,
             initialized: false,
+            status: SearchStatus::Running,
+            last_failure: None,
         }
     }
-}
 
-impl<V> Iterator for Solver<V>
-where
-    V: Variable,
-{
-    type Item = VarSet<V>;
+    pub fn with_limits(mut self, limits: SearchLimits) -> Solver<V> {
+        self.limits = limits;
+        self
+    }
 
-    fn next(&mut self) -> Option<VarSet<V>> {
-        if !self.initialized {
-            if let Err(_) = self.state_stack[0].initial_propagation() {
-                return None;
-            }
-            self.initialized = true;
+    /// Makes the search stop once `token` is cancelled. Every solver starts
+    /// with a token of its own, which can be fetched with `cancel_token`.
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Solver<V> {
+        self.state.cancel_token = token;
+        self
+    }
+
+    pub fn cancel_token(&self) -> CancelToken {
+        self.state.cancel_token.clone()
+    }
+
+    /// Like `next`, but tells an interrupted search apart from one that has
+    /// run out of solutions or hit a limit.
+    pub fn try_next(&mut self) -> Result<Option<Solution<V>>, Interrupted> {
+        let next = self.next();
+        if self.status == SearchStatus::Interrupted {
+            return Err(Interrupted);
         }
-        while let Some(current_state) = self.state_stack.pop() {
-            if let Some(next_var) = current_state.choose_var() {
-                if let Some(next_value) = current_state.choose_value(next_var).cloned() {
-                    let (instantiated_state, removed_state) =
-                        current_state.instantiate(next_var, &next_value);
-                    if let Some(state) = removed_state {
-                        self.state_stack.push(state);
-                    }
-                    if let Some(state) = instantiated_state {
-                        self.state_stack.push(state);
+        Ok(next)
+    }
+
+    /// Tells whether the search can continue, and if not, whether that is
+    /// because it is complete or because it hit a limit.
+    pub fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    /// Sets the heuristic choosing which variable to branch on next. The
+    /// default is `FirstFail`.
+    pub fn with_var_selector<S>(mut self, selector: S) -> Solver<V>
+    where
+        S: VarSelector<V> + 'static,
+    {
+        self.var_selector = Box::new(selector);
+        self
+    }
+
+    /// Sets how choice points are split into alternatives. The default is
+    /// `BinaryBranching`.
+    pub fn with_branching<B>(mut self, branching: B) -> Solver<V>
+    where
+        B: Branching<V> + 'static,
+    {
+        self.branching = Box::new(branching);
+        self
+    }
+
+    /// Sets the heuristic choosing the first branch for every variable that
+    /// has not been given its own with `with_value_selector_for`. The default
+    /// is `FirstValue`.
+    pub fn with_value_selector<S>(mut self, selector: S) -> Solver<V>
+    where
+        S: ValueSelector<V> + 'static,
+    {
+        self.value_selectors[0] = Box::new(selector);
+        self
+    }
+
+    /// Sets the value heuristic for a group of variables. The group shares a
+    /// single selector, so stateful selectors like `Random` draw from one
+    /// sequence for the whole group.
+    pub fn with_value_selector_for<I, Q, S>(mut self, var_ids: I, selector: S) -> Solver<V>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<VarId>,
+        S: ValueSelector<V> + 'static,
+    {
+        let index = self.value_selectors.len();
+        self.value_selectors.push(Box::new(selector));
+        for var_id in var_ids {
+            self.value_selector_of.insert(*var_id.borrow(), index);
+        }
+        self
+    }
+
+    /// What the search has done so far.
+    pub fn stats(&self) -> &SolverStats {
+        &self.state.stats
+    }
+
+    pub fn var_name(&self, var_id: VarId) -> Option<&str> {
+        self.state.var_set.name(var_id)
+    }
+
+    pub fn prop_label(&self, prop_id: PropId) -> Option<&str> {
+        self.state.prop_set.label(prop_id)
+    }
+
+    /// Describes a failure using the names of its variable and propagator.
+    pub fn describe_failure(&self, failure: &Failure) -> String {
+        failure.describe(
+            |var_id| self.state.var_set.describe(var_id),
+            |prop_id| self.state.prop_set.describe(prop_id),
+        )
+    }
+
+    /// A readable summary of `stats`, with the propagation counts listed
+    /// under the labels of the propagators.
+    pub fn stats_report(&self) -> String {
+        let stats = &self.state.stats;
+        let mut report = format!(
+            "nodes: {}\nfailures: {}\nbacktracks: {}\nsolutions: {}\nmax depth: {}\n\
+             propagation time: {:?}\nsearch time: {:?}\npropagations:\n",
+            stats.nodes,
+            stats.failures,
+            stats.backtracks,
+            stats.solutions,
+            stats.max_depth,
+            stats.propagation_time,
+            stats.search_time,
+        );
+        for &prop_id in self.state.prop_set.prop_ids() {
+            let count = stats.propagations.get(&prop_id).cloned().unwrap_or(0);
+            let label = self.state.prop_set.describe(prop_id);
+            report += &format!("    {}: {}\n", label, count);
+        }
+        report
+    }
+
+    /// The most recent failure met by the search. If the initial propagation
+    /// fails, this tells which variable and propagator made the model
+    /// infeasible.
+    pub fn last_failure(&self) -> Option<Failure> {
+        self.last_failure
+    }
+
+    /// The value of the objective in the best solution found so far.
+    pub fn best_objective(&self) -> Option<&V::Value> {
+        self.objective.as_ref().and_then(|o| o.bound.as_ref())
+    }
+
+    /// Whether the last solution returned is known to be optimal, that is
+    /// the search has finished after finding at least one solution.
+    pub fn optimality_proven(&self) -> bool {
+        self.status == SearchStatus::Exhausted && self.best_objective().is_some()
+    }
+
+    fn reached_limit(&self) -> Option<Limit> {
+        let limits = &self.limits;
+        let stats = &self.state.stats;
+        if limits.max_nodes.is_some_and(|max| stats.nodes >= max) {
+            return Some(Limit::Nodes);
+        }
+        if limits.max_failures.is_some_and(|max| stats.failures >= max) {
+            return Some(Limit::Failures);
+        }
+        match (limits.time_limit, self.started) {
+            (Some(limit), Some(started)) if started.elapsed() >= limit => Some(Limit::Time),
+            _ => None,
+        }
+    }
+
+    /// Sets the status to `Interrupted` if the search has been cancelled.
+    /// Propagation fails when interrupted, so this is checked before a
+    /// failure is counted.
+    fn interrupted(&mut self) -> bool {
+        if self.state.cancel_token.is_cancelled() {
+            self.status = SearchStatus::Interrupted;
+        }
+        self.status == SearchStatus::Interrupted
+    }
+
+    /// Ends the search. The status is left alone if a limit already set it.
+    fn finish(&mut self) {
+        if self.status == SearchStatus::Running {
+            self.status = SearchStatus::Exhausted;
+        }
+    }
+
+    /// Restricts the objective to values better than the best solution so
+    /// far. Called at every new node, so the bound is undone on backtrack
+    /// along with everything else and re-imposed on the next branch.
+    fn apply_objective(&mut self) -> Result<(), Failure> {
+        if let Some(Objective {
+            var_id,
+            bound: Some(ref bound),
+            tighten,
+        }) = self.objective
+        {
+            let update = tighten(self.state.var_set.var_mut(var_id), bound)
+                .map_err(|failure| failure.with_reason("cannot improve on the objective"))?;
+            self.state.propagate(update)?;
+        }
+        Ok(())
+    }
+
+    /// Moves to the next unexplored node: applies the next alternative of
+    /// the deepest choice point, backing out of choice points that have run
+    /// out of alternatives. Returns `false` once the whole tree is explored
+    /// or a limit is reached, in which case `status` is set accordingly.
+    fn advance(&mut self) -> bool {
+        loop {
+            let decision = match self.choice_points.last_mut() {
+                Some(choice_point) => {
+                    if choice_point.active {
+                        self.state.pop_level();
+                        self.state.stats.backtracks += 1;
+                        choice_point.active = false;
                     }
-                } else {
+                    choice_point.alternatives.pop()
+                }
+                None => return false,
+            };
+            let decision = match decision {
+                Some(decision) => decision,
+                None => {
+                    self.choice_points.pop();
                     continue;
                 }
-            } else {
-                return Some(current_state.var_set);
+            };
+
+            if self.interrupted() {
+                return false;
+            }
+            if let Some(limit) = self.reached_limit() {
+                self.status = SearchStatus::LimitReached(limit);
+                return false;
+            }
+            self.state.stats.nodes += 1;
+            self.state.push_level();
+            let depth = self.state.depth();
+            if depth > self.state.stats.max_depth {
+                self.state.stats.max_depth = depth;
+            }
+            self.choice_points.last_mut().unwrap().active = true;
+            let failure = match self.state.apply(&decision).and_then(|()| self.apply_objective()) {
+                Ok(()) => return true,
+                Err(failure) => failure,
+            };
+            if self.interrupted() {
+                return false;
+            }
+            self.state.stats.failures += 1;
+            self.last_failure = Some(failure);
+        }
+    }
+
+    /// Runs the search up to the next solution. `next` wraps this to keep
+    /// track of the time spent.
+    fn search(&mut self) -> Option<Solution<V>> {
+        if self.status != SearchStatus::Running {
+            return None;
+        }
+        if self.limits.max_solutions.is_some_and(|max| self.state.stats.solutions >= max) {
+            self.status = SearchStatus::LimitReached(Limit::Solutions);
+            return None;
+        }
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+
+        let consistent = if !self.initialized {
+            self.initialized = true;
+            match self.state.initial_propagation() {
+                Ok(()) => true,
+                Err(failure) => {
+                    if !self.interrupted() {
+                        self.state.stats.failures += 1;
+                        self.last_failure = Some(failure);
+                    }
+                    false
+                }
+            }
+        } else {
+            self.advance()
+        };
+        if !consistent {
+            self.finish();
+            return None;
+        }
+
+        while let Some(next_var) = self.var_selector.select(&self.state.var_set) {
+            let index = self.value_selector_of.get(&next_var).cloned().unwrap_or(0);
+            let mut alternatives = self.branching.branch(
+                self.state.var_set.var(next_var),
+                &mut *self.value_selectors[index],
+            );
+            alternatives.reverse();
+            self.choice_points.push(ChoicePoint {
+                alternatives,
+                active: false,
+            });
+            if !self.advance() {
+                self.finish();
+                return None;
             }
         }
-        None
+        let solution = Solution::from_var_set(&self.state.var_set);
+        if solution.is_none() {
+            self.finish();
+            return None;
+        }
+        if let Some(ref mut objective) = self.objective {
+            objective.bound = self.state.var_set.var(objective.var_id).value().cloned();
+        }
+        self.state.stats.solutions += 1;
+        solution
+    }
+}
+
+impl<V> Iterator for Solver<V>
+where
+    V: Variable,
+{
+    type Item = Solution<V>;
+
+    fn next(&mut self) -> Option<Solution<V>> {
+        let started = Instant::now();
+        let propagation_time = self.state.stats.propagation_time;
+        let result = self.search();
+        let stats = &mut self.state.stats;
+        let propagating = stats.propagation_time - propagation_time;
+        stats.search_time += started.elapsed().checked_sub(propagating).unwrap_or_default();
+        result
+    }
+}
+
+impl<V> Solver<V>
+where
+    V: OrderedVariable,
+{
+    /// Turns the solver into a branch-and-bound search for the minimum of
+    /// `var_id`. Each solution returned is strictly better than the previous
+    /// one; once the iterator is exhausted the last one is optimal.
+    pub fn minimize(mut self, var_id: VarId) -> Solver<V> {
+        self.objective = Some(Objective {
+            var_id,
+            bound: None,
+            tighten: remove_at_least::<V>,
+        });
+        self
+    }
+
+    /// Like `minimize`, but searches for the maximum of `var_id`.
+    pub fn maximize(mut self, var_id: VarId) -> Solver<V> {
+        self.objective = Some(Objective {
+            var_id,
+            bound: None,
+            tighten: remove_at_most::<V>,
+        });
+        self
     }
 }
 
@@ -0,0 +1,132 @@
+/// An undo log for a vector of items, such as the variables of a `VarSet`.
+///
+/// Before an item is modified at a search level it is saved with `save`; the
+/// first save of an item at each level copies it onto the trail, later saves
+/// at the same level are free. Popping a level copies every saved item back,
+/// which restores the vector to the state it had when the level was pushed.
+/// Modifications made while no level is pushed are permanent.
+#[derive(Clone, Debug)]
+pub struct Trail<T> {
+    entries: Vec<Entry<T>>,
+    levels: Vec<Level>,
+    stamps: Vec<usize>,
+    next_stamp: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Entry<T> {
+    index: usize,
+    item: T,
+    stamp: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Level {
+    mark: usize,
+    stamp: usize,
+}
+
+impl<T> Default for Trail<T>
+where
+    T: Clone,
+{
+    fn default() -> Trail<T> {
+        Trail::new()
+    }
+}
+
+impl<T> Trail<T>
+where
+    T: Clone,
+{
+    pub fn new() -> Trail<T> {
+        Trail {
+            entries: vec![],
+            levels: vec![],
+            stamps: vec![],
+            next_stamp: 1,
+        }
+    }
+
+    /// The number of levels currently pushed.
+    pub fn depth(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn push_level(&mut self) {
+        self.levels.push(Level {
+            mark: self.entries.len(),
+            stamp: self.next_stamp,
+        });
+        self.next_stamp += 1;
+    }
+
+    /// Restores every item saved since the matching `push_level`.
+    pub fn pop_level(&mut self, items: &mut [T]) {
+        let level = self.levels.pop().expect("no trail level to pop");
+        while self.entries.len() > level.mark {
+            let entry = self.entries.pop().unwrap();
+            self.stamps[entry.index] = entry.stamp;
+            items[entry.index] = entry.item;
+        }
+    }
+
+    /// Records `item`, stored at `index`, so that it can be restored when the
+    /// current level is popped.
+    pub fn save(&mut self, index: usize, item: &T) {
+        let stamp = match self.levels.last() {
+            Some(level) => level.stamp,
+            None => return,
+        };
+        if index >= self.stamps.len() {
+            self.stamps.resize(index + 1, 0);
+        }
+        if self.stamps[index] != stamp {
+            self.entries.push(Entry {
+                index,
+                item: item.clone(),
+                stamp: self.stamps[index],
+            });
+            self.stamps[index] = stamp;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Trail;
+
+    #[test]
+    fn restores_saved_items() {
+        let mut items = vec![0, 1, 2];
+        let mut trail = Trail::new();
+
+        trail.save(0, &items[0]);
+        items[0] = 10;
+
+        trail.push_level();
+        trail.save(1, &items[1]);
+        items[1] = 11;
+        trail.save(1, &items[1]);
+        items[1] = 21;
+
+        trail.push_level();
+        trail.save(1, &items[1]);
+        items[1] = 31;
+        trail.save(2, &items[2]);
+        items[2] = 32;
+
+        trail.pop_level(&mut items);
+        assert_eq!(items, vec![10, 21, 2]);
+
+        trail.push_level();
+        trail.save(1, &items[1]);
+        items[1] = 41;
+        trail.pop_level(&mut items);
+        assert_eq!(items, vec![10, 21, 2]);
+
+        trail.pop_level(&mut items);
+        assert_eq!(items, vec![10, 1, 2]);
+        assert_eq!(trail.depth(), 0);
+    }
+}
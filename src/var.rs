@@ -1,34 +1,168 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ops::Bound::{Excluded, Unbounded};
 use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::iter;
+use std::mem;
+use std::sync::Arc;
 
 use propagate::PropId;
+use trail::Trail;
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct VarId {
     id: usize,
 }
 
+impl VarId {
+    /// The position of the variable in its `VarSet`.
+    pub(crate) fn index(&self) -> usize {
+        self.id
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub enum DomainUpdate {
     Unchanged(VarId),
     Reduced(VarId),
+    LowerBound(VarId),
+    UpperBound(VarId),
+    Bounds(VarId),
     Fixed(VarId),
 }
 
 impl DomainUpdate {
     pub fn var_id(&self) -> VarId {
-        match self {
-            &DomainUpdate::Unchanged(v) => v,
-            &DomainUpdate::Reduced(v) => v,
-            &DomainUpdate::Fixed(v) => v,
+        match *self {
+            DomainUpdate::Unchanged(v) => v,
+            DomainUpdate::Reduced(v) => v,
+            DomainUpdate::LowerBound(v) => v,
+            DomainUpdate::UpperBound(v) => v,
+            DomainUpdate::Bounds(v) => v,
+            DomainUpdate::Fixed(v) => v,
+        }
+    }
+}
+
+/// Why a domain operation or propagator failed. Domain operations report
+/// the variable they emptied; the search fills in the propagator that was
+/// running at the time.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Failure {
+    pub var_id: Option<VarId>,
+    pub prop_id: Option<PropId>,
+    pub reason: Option<&'static str>,
+}
+
+impl Failure {
+    /// The failure of an operation that would leave `var_id` with an empty
+    /// domain.
+    pub fn wipe_out(var_id: VarId) -> Failure {
+        Failure {
+            var_id: Some(var_id),
+            prop_id: None,
+            reason: None,
+        }
+    }
+
+    /// A failure detected without emptying any single domain, such as a
+    /// constraint whose variables are all fixed to values violating it.
+    pub fn reason(reason: &'static str) -> Failure {
+        Failure {
+            var_id: None,
+            prop_id: None,
+            reason: Some(reason),
+        }
+    }
+
+    pub fn with_prop(mut self, prop_id: PropId) -> Failure {
+        self.prop_id = Some(prop_id);
+        self
+    }
+
+    pub fn with_reason(mut self, reason: &'static str) -> Failure {
+        self.reason = Some(reason);
+        self
+    }
+
+    /// Formats the failure like `Display` does, but with the variable and
+    /// propagator named by the given functions.
+    pub fn describe<F, G>(&self, var_name: F, prop_name: G) -> String
+    where
+        F: Fn(VarId) -> String,
+        G: Fn(PropId) -> String,
+    {
+        let mut description = match self.var_id {
+            Some(var_id) => format!("domain of {} wiped out", var_name(var_id)),
+            None => "inconsistency".to_string(),
+        };
+        if let Some(prop_id) = self.prop_id {
+            description += &format!(" by {}", prop_name(prop_id));
+        }
+        if let Some(reason) = self.reason {
+            description += &format!(": {}", reason);
         }
+        description
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = self.describe(
+            |var_id| format!("{:?}", var_id),
+            |prop_id| format!("{:?}", prop_id),
+        );
+        write!(f, "{}", description)
     }
 }
 
-pub type VarResult<T> = Result<T, ()>;
+impl Error for Failure {}
 
-pub trait Variable: Clone {
-    type Value: Clone;
+pub type VarResult<T> = Result<T, Failure>;
+
+/// Integral value types that can be stored in interval and bitset domains.
+///
+/// Values are converted to `i64` for arithmetic. Conversions back saturate at
+/// the bounds of the type, which keeps bound updates like "remove everything
+/// below -3" meaningful for unsigned types.
+pub trait Integer: Copy + Ord + Hash + Debug + Send + Sync {
+    fn to_i64(self) -> i64;
+    fn from_i64(value: i64) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                fn to_i64(self) -> i64 {
+                    self as i64
+                }
+
+                fn from_i64(value: i64) -> $t {
+                    if value < <$t>::MIN as i64 {
+                        <$t>::MIN
+                    } else if value > <$t>::MAX as i64 {
+                        <$t>::MAX
+                    } else {
+                        value as $t
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_integer!(i8, i16, i32, i64, isize, u8, u16, u32, usize);
+
+/// A decision variable and its domain.
+///
+/// Variables are `Send` and their values `Send` and `Sync`, and propagators
+/// and heuristics must be `Send` too, so a `Solver` can be moved to another
+/// thread.
+pub trait Variable: Clone + Send {
+    type Value: Clone + Send + Sync;
 
     fn with_domain<I, Q>(values: I) -> Self
     where
@@ -41,12 +175,59 @@ pub trait Variable: Clone {
     fn size(&self) -> usize;
     fn contains(&self, value: &Self::Value) -> bool;
     fn value(&self) -> Option<&Self::Value>;
-    fn possibilities<'a>(&'a self) -> Box<Iterator<Item = &Self::Value> + 'a>;
+    fn possibilities<'a>(&'a self) -> Box<dyn Iterator<Item = Self::Value> + 'a>;
 
     fn remove(&mut self, value: &Self::Value) -> VarResult<DomainUpdate>;
     fn instantiate(&mut self, value: &Self::Value) -> VarResult<DomainUpdate>;
 }
 
+/// Variables whose values are totally ordered, allowing bounds reasoning.
+///
+/// All ranges are inclusive. A failed operation leaves the domain empty or
+/// otherwise unusable, just like `Variable::remove`; outside of a failed
+/// search state domains are never empty, so `min` and `max` always exist.
+pub trait OrderedVariable: Variable {
+    fn min(&self) -> &Self::Value;
+    fn max(&self) -> &Self::Value;
+
+    /// Removes every value strictly less than `value`.
+    fn remove_below(&mut self, value: &Self::Value) -> VarResult<DomainUpdate>;
+    /// Removes every value strictly greater than `value`.
+    fn remove_above(&mut self, value: &Self::Value) -> VarResult<DomainUpdate>;
+    /// Removes every value in `lo..=hi`. Does nothing if `lo > hi`.
+    fn remove_range(&mut self, lo: &Self::Value, hi: &Self::Value) -> VarResult<DomainUpdate>;
+    /// Removes every value outside of `lo..=hi`.
+    fn restrict_to(&mut self, lo: &Self::Value, hi: &Self::Value) -> VarResult<DomainUpdate>;
+}
+
+/// Classifies a change to an ordered variable by comparing its domain to the
+/// bounds and size it had before the change.
+fn ordered_update<V>(
+    var: &V,
+    old_min: &V::Value,
+    old_max: &V::Value,
+    old_size: usize,
+) -> DomainUpdate
+where
+    V: OrderedVariable,
+    V::Value: PartialEq,
+{
+    let id = var.id();
+    let size = var.size();
+    if size == old_size {
+        DomainUpdate::Unchanged(id)
+    } else if size == 1 {
+        DomainUpdate::Fixed(id)
+    } else {
+        match (var.min() != old_min, var.max() != old_max) {
+            (true, true) => DomainUpdate::Bounds(id),
+            (true, false) => DomainUpdate::LowerBound(id),
+            (false, true) => DomainUpdate::UpperBound(id),
+            (false, false) => DomainUpdate::Reduced(id),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct BTreeSetVar<T> {
     id: VarId,
@@ -55,7 +236,7 @@ pub struct BTreeSetVar<T> {
 
 impl<T> Variable for BTreeSetVar<T>
 where
-    T: Clone + Ord,
+    T: Clone + Ord + Send + Sync,
 {
     type Value = T;
 
@@ -68,7 +249,7 @@ where
         domain.extend(values.into_iter().map(|q| q.borrow().clone()));
         BTreeSetVar {
             id: VarId::default(),
-            domain: domain,
+            domain,
         }
     }
 
@@ -96,20 +277,17 @@ where
         }
     }
 
-    fn possibilities<'a>(&'a self) -> Box<Iterator<Item = &Self::Value> + 'a> {
-        Box::new(self.domain.iter())
+    fn possibilities<'a>(&'a self) -> Box<dyn Iterator<Item = Self::Value> + 'a> {
+        Box::new(self.domain.iter().cloned())
     }
 
     fn remove(&mut self, value: &Self::Value) -> VarResult<DomainUpdate> {
-        if self.domain.remove(value) {
-            match self.size() {
-                0 => Err(()),
-                1 => Ok(DomainUpdate::Fixed(self.id)),
-                _ => Ok(DomainUpdate::Reduced(self.id)),
-            }
-        } else {
-            Ok(DomainUpdate::Unchanged(self.id))
+        if !self.domain.is_empty() && !self.domain.contains(value) {
+            return Ok(DomainUpdate::Unchanged(self.id));
         }
+        self.update(|domain| {
+            domain.remove(value);
+        })
     }
 
     fn instantiate(&mut self, value: &Self::Value) -> VarResult<DomainUpdate> {
@@ -122,11 +300,92 @@ where
                 Ok(DomainUpdate::Fixed(self.id))
             }
         } else {
-            Err(())
+            Err(Failure::wipe_out(self.id))
+        }
+    }
+}
+
+impl<T> BTreeSetVar<T>
+where
+    T: Clone + Ord + Send + Sync,
+{
+    /// Applies `change` to the domain and classifies the result. The changes
+    /// only touch the values they remove, so narrowing a large domain by a
+    /// few values stays cheap.
+    fn update<F>(&mut self, change: F) -> VarResult<DomainUpdate>
+    where
+        F: FnOnce(&mut BTreeSet<T>),
+    {
+        if self.domain.is_empty() {
+            return Err(Failure::wipe_out(self.id));
+        }
+        let old_min = self.min().clone();
+        let old_max = self.max().clone();
+        let old_size = self.size();
+
+        change(&mut self.domain);
+        if self.domain.is_empty() {
+            Err(Failure::wipe_out(self.id))
+        } else {
+            Ok(ordered_update(self, &old_min, &old_max, old_size))
+        }
+    }
+
+    /// Drops every value of `domain` strictly less than `value`.
+    fn drop_below(domain: &mut BTreeSet<T>, value: &T) {
+        let kept = domain.split_off(value);
+        *domain = kept;
+    }
+
+    /// Drops every value of `domain` strictly greater than `value`.
+    fn drop_above(domain: &mut BTreeSet<T>, value: &T) {
+        let first = domain.range((Excluded(value), Unbounded)).next().cloned();
+        if let Some(first) = first {
+            domain.split_off(&first);
         }
     }
 }
 
+impl<T> OrderedVariable for BTreeSetVar<T>
+where
+    T: Clone + Ord + Send + Sync,
+{
+    fn min(&self) -> &T {
+        self.domain.iter().next().unwrap()
+    }
+
+    fn max(&self) -> &T {
+        self.domain.iter().next_back().unwrap()
+    }
+
+    fn remove_below(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        self.update(|domain| BTreeSetVar::drop_below(domain, value))
+    }
+
+    fn remove_above(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        self.update(|domain| BTreeSetVar::drop_above(domain, value))
+    }
+
+    fn remove_range(&mut self, lo: &T, hi: &T) -> VarResult<DomainUpdate> {
+        if lo > hi {
+            return Ok(DomainUpdate::Unchanged(self.id));
+        }
+        self.update(|domain| {
+            let removed: Vec<T> = domain.range(lo..=hi).cloned().collect();
+            for value in &removed {
+                domain.remove(value);
+            }
+        })
+    }
+
+    fn restrict_to(&mut self, lo: &T, hi: &T) -> VarResult<DomainUpdate> {
+        self.update(|domain| {
+            BTreeSetVar::drop_below(domain, lo);
+            BTreeSetVar::drop_above(domain, hi);
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct HashSetVar<T>
 where
@@ -138,7 +397,7 @@ where
 
 impl<T> Variable for HashSetVar<T>
 where
-    T: Clone + Eq + ::std::hash::Hash,
+    T: Clone + Eq + ::std::hash::Hash + Send + Sync,
 {
     type Value = T;
 
@@ -151,7 +410,7 @@ where
         domain.extend(values.into_iter().map(|q| q.borrow().clone()));
         HashSetVar {
             id: VarId::default(),
-            domain: domain,
+            domain,
         }
     }
 
@@ -179,14 +438,14 @@ where
         }
     }
 
-    fn possibilities<'a>(&'a self) -> Box<Iterator<Item = &Self::Value> + 'a> {
-        Box::new(self.domain.iter())
+    fn possibilities<'a>(&'a self) -> Box<dyn Iterator<Item = Self::Value> + 'a> {
+        Box::new(self.domain.iter().cloned())
     }
 
     fn remove(&mut self, value: &Self::Value) -> VarResult<DomainUpdate> {
         if self.domain.remove(value) {
             match self.size() {
-                0 => Err(()),
+                0 => Err(Failure::wipe_out(self.id)),
                 1 => Ok(DomainUpdate::Fixed(self.id)),
                 _ => Ok(DomainUpdate::Reduced(self.id)),
             }
@@ -205,8 +464,542 @@ where
                 Ok(DomainUpdate::Fixed(self.id))
             }
         } else {
-            Err(())
+            Err(Failure::wipe_out(self.id))
+        }
+    }
+}
+
+/// A variable over a range of integers.
+///
+/// Only the bounds of the domain are stored, so a domain like `0..1_000_000`
+/// is as cheap to create and clone as a small one. Values removed from the
+/// inside of the range are remembered as holes, kept as disjoint ranges that
+/// are merged when they touch, so removing a long stretch of values costs no
+/// more than removing one. The hole map stays empty (and unallocated) until
+/// such a removal actually happens.
+#[derive(Clone, Debug)]
+pub struct IntervalVar<T>
+where
+    T: Integer,
+{
+    id: VarId,
+    min: T,
+    max: T,
+    /// The removed ranges strictly inside the bounds, as inclusive `lo => hi`
+    /// pairs that neither overlap nor touch.
+    holes: BTreeMap<T, T>,
+    /// The number of values inside the holes.
+    missing: u64,
+}
+
+impl<T> IntervalVar<T>
+where
+    T: Integer,
+{
+    pub fn new(min: T, max: T) -> IntervalVar<T> {
+        assert!(min <= max, "IntervalVar requires a non-empty domain");
+        IntervalVar {
+            id: VarId::default(),
+            min,
+            max,
+            holes: BTreeMap::new(),
+            missing: 0,
+        }
+    }
+
+    /// The removed ranges strictly inside the bounds, as inclusive `lo => hi`
+    /// pairs.
+    pub fn holes(&self) -> &BTreeMap<T, T> {
+        &self.holes
+    }
+
+    fn step(value: T, delta: i64) -> T {
+        T::from_i64(value.to_i64() + delta)
+    }
+
+    fn len(lo: T, hi: T) -> u64 {
+        (hi.to_i64() - lo.to_i64()) as u64 + 1
+    }
+
+    /// Removes `lo..=hi`, which must lie strictly inside the bounds.
+    fn add_hole(&mut self, lo: T, hi: T) {
+        let (mut lo, mut hi) = (lo, hi);
+        let before = self.holes
+            .range(..=lo)
+            .next_back()
+            .map(|(&start, &end)| (start, end));
+        if let Some((start, end)) = before {
+            if end >= IntervalVar::step(lo, -1) {
+                self.holes.remove(&start);
+                self.missing -= IntervalVar::len(start, end);
+                lo = start;
+                hi = hi.max(end);
+            }
+        }
+        loop {
+            let after = self.holes
+                .range(lo..=IntervalVar::step(hi, 1))
+                .next()
+                .map(|(&start, &end)| (start, end));
+            match after {
+                Some((start, end)) => {
+                    self.holes.remove(&start);
+                    self.missing -= IntervalVar::len(start, end);
+                    hi = hi.max(end);
+                }
+                None => break,
+            }
+        }
+        self.holes.insert(lo, hi);
+        self.missing += IntervalVar::len(lo, hi);
+    }
+
+    /// Raises the lower bound to `value`, which must lie within the domain's
+    /// current bounds.
+    fn raise_min(&mut self, value: T) {
+        let kept = self.holes.split_off(&value);
+        let dropped = mem::replace(&mut self.holes, kept);
+        self.min = value;
+        for (&start, &end) in &dropped {
+            self.missing -= IntervalVar::len(start, end);
+            if end >= value {
+                self.min = IntervalVar::step(end, 1);
+            }
+        }
+        if let Some(end) = self.holes.remove(&self.min) {
+            self.missing -= IntervalVar::len(self.min, end);
+            self.min = IntervalVar::step(end, 1);
+        }
+    }
+
+    /// Lowers the upper bound to `value`, which must lie within the domain's
+    /// current bounds.
+    fn lower_max(&mut self, value: T) {
+        let dropped = self.holes.split_off(&IntervalVar::step(value, 1));
+        for (&start, &end) in &dropped {
+            self.missing -= IntervalVar::len(start, end);
+        }
+        self.max = value;
+        let last = self.holes.iter().next_back().map(|(&start, &end)| (start, end));
+        if let Some((start, end)) = last {
+            if end >= value {
+                self.holes.remove(&start);
+                self.missing -= IntervalVar::len(start, end);
+                self.max = IntervalVar::step(start, -1);
+            }
+        }
+    }
+}
+
+impl<T> Variable for IntervalVar<T>
+where
+    T: Integer,
+{
+    type Value = T;
+
+    fn with_domain<I, Q>(values: I) -> IntervalVar<T>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<T>,
+    {
+        let mut values = values.into_iter().map(|q| q.borrow().to_i64());
+        let first = values.next().expect("IntervalVar requires a non-empty domain");
+
+        // ranges are by far the most common domain, so avoid collecting
+        // them into memory: only the runs of consecutive values are kept,
+        // and the gaps between them become holes
+        let mut runs = vec![];
+        let mut run = (first, first);
+        for value in values {
+            if value == run.1 + 1 {
+                run.1 = value;
+            } else {
+                runs.push(run);
+                run = (value, value);
+            }
+        }
+        runs.push(run);
+        runs.sort();
+
+        let max = runs.iter().map(|&(_, hi)| hi).max().unwrap();
+        let mut var = IntervalVar::new(T::from_i64(runs[0].0), T::from_i64(max));
+        let mut reached = runs[0].1;
+        for &(lo, hi) in &runs[1..] {
+            if lo > reached + 1 {
+                var.add_hole(T::from_i64(reached + 1), T::from_i64(lo - 1));
+            }
+            reached = reached.max(hi);
+        }
+        var
+    }
+
+    fn id(&self) -> VarId {
+        self.id
+    }
+
+    fn set_id(&mut self, id: VarId) {
+        self.id = id;
+    }
+
+    fn size(&self) -> usize {
+        (IntervalVar::len(self.min, self.max) - self.missing) as usize
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        self.min <= *value
+            && *value <= self.max
+            && self.holes.range(..=*value).next_back().is_none_or(|(_, &end)| end < *value)
+    }
+
+    fn value(&self) -> Option<&T> {
+        if self.min == self.max {
+            Some(&self.min)
+        } else {
+            None
+        }
+    }
+
+    fn possibilities<'a>(&'a self) -> Box<dyn Iterator<Item = T> + 'a> {
+        // the stretches between the holes
+        let starts = iter::once(self.min.to_i64())
+            .chain(self.holes.values().map(|end| end.to_i64() + 1));
+        let ends = self.holes
+            .keys()
+            .map(|start| start.to_i64() - 1)
+            .chain(iter::once(self.max.to_i64()));
+        Box::new(starts.zip(ends).flat_map(|(lo, hi)| (lo..=hi).map(T::from_i64)))
+    }
+
+    fn remove(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        if !self.contains(value) {
+            return Ok(DomainUpdate::Unchanged(self.id));
+        }
+        if self.min == self.max {
+            return Err(Failure::wipe_out(self.id));
+        }
+
+        let (old_min, old_max, old_size) = (self.min, self.max, self.size());
+        if *value == self.min {
+            self.raise_min(IntervalVar::step(*value, 1));
+        } else if *value == self.max {
+            self.lower_max(IntervalVar::step(*value, -1));
+        } else {
+            self.add_hole(*value, *value);
+        }
+        Ok(ordered_update(self, &old_min, &old_max, old_size))
+    }
+
+    fn instantiate(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        if self.contains(value) {
+            if self.min == self.max {
+                Ok(DomainUpdate::Unchanged(self.id))
+            } else {
+                self.min = *value;
+                self.max = *value;
+                self.holes.clear();
+                self.missing = 0;
+                Ok(DomainUpdate::Fixed(self.id))
+            }
+        } else {
+            Err(Failure::wipe_out(self.id))
+        }
+    }
+}
+
+impl<T> OrderedVariable for IntervalVar<T>
+where
+    T: Integer,
+{
+    fn min(&self) -> &T {
+        &self.min
+    }
+
+    fn max(&self) -> &T {
+        &self.max
+    }
+
+    fn remove_below(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        self.restrict_to(value, &self.max.clone())
+    }
+
+    fn remove_above(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        self.restrict_to(&self.min.clone(), value)
+    }
+
+    fn remove_range(&mut self, lo: &T, hi: &T) -> VarResult<DomainUpdate> {
+        if lo > hi || *hi < self.min || *lo > self.max {
+            return Ok(DomainUpdate::Unchanged(self.id));
+        }
+        if *lo <= self.min && *hi >= self.max {
+            return Err(Failure::wipe_out(self.id));
+        }
+        if *lo <= self.min {
+            return self.remove_below(&IntervalVar::step(*hi, 1));
+        }
+        if *hi >= self.max {
+            return self.remove_above(&IntervalVar::step(*lo, -1));
+        }
+
+        let old_size = self.size();
+        self.add_hole(*lo, *hi);
+        if self.size() == old_size {
+            Ok(DomainUpdate::Unchanged(self.id))
+        } else {
+            Ok(DomainUpdate::Reduced(self.id))
+        }
+    }
+
+    fn restrict_to(&mut self, lo: &T, hi: &T) -> VarResult<DomainUpdate> {
+        if lo > hi || *hi < self.min || *lo > self.max {
+            return Err(Failure::wipe_out(self.id));
+        }
+        let (old_min, old_max, old_size) = (self.min, self.max, self.size());
+        if *lo > self.min {
+            self.raise_min(*lo);
+        }
+        if *hi < self.max {
+            self.lower_max(*hi);
+        }
+        if self.min > self.max {
+            Err(Failure::wipe_out(self.id))
+        } else {
+            Ok(ordered_update(self, &old_min, &old_max, old_size))
+        }
+    }
+}
+
+/// A variable over a small range of integers, stored as a bit vector.
+///
+/// Bit `i` is set when `offset + i` is still in the domain. Cloning is a
+/// copy of a few machine words, which makes this the cheapest variable to
+/// branch on for puzzle-sized domains.
+#[derive(Clone, Debug)]
+pub struct BitSetVar<T>
+where
+    T: Integer,
+{
+    id: VarId,
+    offset: i64,
+    words: Vec<u64>,
+    min: T,
+    max: T,
+}
+
+impl<T> BitSetVar<T>
+where
+    T: Integer,
+{
+    fn bit(&self, value: T) -> Option<(usize, u64)> {
+        let index = value.to_i64() - self.offset;
+        if index < 0 || index >= self.words.len() as i64 * 64 {
+            None
+        } else {
+            Some((index as usize / 64, 1 << (index % 64)))
+        }
+    }
+
+    fn value_at(&self, word: usize, bit: u32) -> T {
+        T::from_i64(self.offset + word as i64 * 64 + i64::from(bit))
+    }
+
+    fn lowest(&self) -> Option<T> {
+        self.words
+            .iter()
+            .position(|&word| word != 0)
+            .map(|w| self.value_at(w, self.words[w].trailing_zeros()))
+    }
+
+    fn highest(&self) -> Option<T> {
+        self.words
+            .iter()
+            .rposition(|&word| word != 0)
+            .map(|w| self.value_at(w, 63 - self.words[w].leading_zeros()))
+    }
+
+    /// Clears every bit for values in `lo..=hi`, a whole word at a time.
+    fn clear_range(&mut self, lo: i64, hi: i64) {
+        let last = self.words.len() as i64 * 64 - 1;
+        let lo = ::std::cmp::max(lo - self.offset, 0);
+        let hi = ::std::cmp::min(hi - self.offset, last);
+        if lo > hi {
+            return;
+        }
+        let (lo_word, hi_word) = (lo as usize / 64, hi as usize / 64);
+        for w in lo_word..=hi_word {
+            let mut mask = !0u64;
+            if w == lo_word {
+                mask &= !0u64 << (lo % 64);
+            }
+            if w == hi_word {
+                mask &= !0u64 >> (63 - hi % 64);
+            }
+            self.words[w] &= !mask;
+        }
+    }
+
+    /// Refreshes the cached bounds after bits have been cleared and reports
+    /// the resulting change.
+    fn finish_update(
+        &mut self,
+        old_min: T,
+        old_max: T,
+        old_size: usize,
+    ) -> VarResult<DomainUpdate> {
+        match (self.lowest(), self.highest()) {
+            (Some(min), Some(max)) => {
+                self.min = min;
+                self.max = max;
+                Ok(ordered_update(self, &old_min, &old_max, old_size))
+            }
+            _ => Err(Failure::wipe_out(self.id)),
+        }
+    }
+}
+
+impl<T> Variable for BitSetVar<T>
+where
+    T: Integer,
+{
+    type Value = T;
+
+    fn with_domain<I, Q>(values: I) -> BitSetVar<T>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Borrow<T>,
+    {
+        let values = values
+            .into_iter()
+            .map(|q| *q.borrow())
+            .collect::<Vec<_>>();
+        let min = *values.iter().min().expect(
+            "BitSetVar requires a non-empty domain",
+        );
+        let max = *values.iter().max().unwrap();
+
+        let width = (max.to_i64() - min.to_i64()) as usize + 1;
+        let mut var = BitSetVar {
+            id: VarId::default(),
+            offset: min.to_i64(),
+            words: vec![0; width.div_ceil(64)],
+            min,
+            max,
+        };
+        for value in values {
+            let (word, mask) = var.bit(value).unwrap();
+            var.words[word] |= mask;
+        }
+        var
+    }
+
+    fn id(&self) -> VarId {
+        self.id
+    }
+
+    fn set_id(&mut self, id: VarId) {
+        self.id = id;
+    }
+
+    fn size(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    fn contains(&self, value: &T) -> bool {
+        match self.bit(*value) {
+            Some((word, mask)) => self.words[word] & mask != 0,
+            None => false,
+        }
+    }
+
+    fn value(&self) -> Option<&T> {
+        if self.min == self.max {
+            Some(&self.min)
+        } else {
+            None
+        }
+    }
+
+    fn possibilities<'a>(&'a self) -> Box<dyn Iterator<Item = T> + 'a> {
+        Box::new(self.words.iter().enumerate().flat_map(move |(w, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| self.value_at(w, bit))
+        }))
+    }
+
+    fn remove(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        if !self.contains(value) {
+            return Ok(DomainUpdate::Unchanged(self.id));
+        }
+        if self.min == self.max {
+            return Err(Failure::wipe_out(self.id));
+        }
+
+        let (old_min, old_max, old_size) = (self.min, self.max, self.size());
+        let (word, mask) = self.bit(*value).unwrap();
+        self.words[word] &= !mask;
+        self.finish_update(old_min, old_max, old_size)
+    }
+
+    fn instantiate(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        if self.contains(value) {
+            if self.min == self.max {
+                Ok(DomainUpdate::Unchanged(self.id))
+            } else {
+                let (word, mask) = self.bit(*value).unwrap();
+                for w in &mut self.words {
+                    *w = 0;
+                }
+                self.words[word] = mask;
+                self.min = *value;
+                self.max = *value;
+                Ok(DomainUpdate::Fixed(self.id))
+            }
+        } else {
+            Err(Failure::wipe_out(self.id))
+        }
+    }
+}
+
+impl<T> OrderedVariable for BitSetVar<T>
+where
+    T: Integer,
+{
+    fn min(&self) -> &T {
+        &self.min
+    }
+
+    fn max(&self) -> &T {
+        &self.max
+    }
+
+    fn remove_below(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        let max = self.max;
+        self.restrict_to(value, &max)
+    }
+
+    fn remove_above(&mut self, value: &T) -> VarResult<DomainUpdate> {
+        let min = self.min;
+        self.restrict_to(&min, value)
+    }
+
+    fn remove_range(&mut self, lo: &T, hi: &T) -> VarResult<DomainUpdate> {
+        let (old_min, old_max, old_size) = (self.min, self.max, self.size());
+        self.clear_range(lo.to_i64(), hi.to_i64());
+        self.finish_update(old_min, old_max, old_size)
+    }
+
+    fn restrict_to(&mut self, lo: &T, hi: &T) -> VarResult<DomainUpdate> {
+        let (old_min, old_max, old_size) = (self.min, self.max, self.size());
+        if *lo > self.min {
+            self.clear_range(old_min.to_i64(), lo.to_i64() - 1);
+        }
+        if *hi < self.max {
+            self.clear_range(hi.to_i64() + 1, old_max.to_i64());
         }
+        self.finish_update(old_min, old_max, old_size)
     }
 }
 
@@ -218,6 +1011,17 @@ where
     vars: Vec<V>,
     var_ids: Vec<VarId>,
     subscriptions: Vec<Vec<PropId>>,
+    names: Arc<Vec<Option<String>>>,
+    trail: Trail<V>,
+}
+
+impl<V> Default for VarSet<V>
+where
+    V: Variable,
+{
+    fn default() -> VarSet<V> {
+        VarSet::new()
+    }
 }
 
 impl<V> VarSet<V>
@@ -229,6 +1033,8 @@ where
             vars: vec![],
             var_ids: vec![],
             subscriptions: vec![],
+            names: Arc::new(vec![]),
+            trail: Trail::new(),
         }
     }
 
@@ -243,9 +1049,35 @@ where
         self.vars.push(var);
         self.var_ids.push(var_id);
         self.subscriptions.push(vec![]);
+        Arc::make_mut(&mut self.names).push(None);
         var_id
     }
 
+    pub fn set_name<S>(&mut self, var_id: VarId, name: S)
+    where
+        S: Into<String>,
+    {
+        Arc::make_mut(&mut self.names)[var_id.id] = Some(name.into());
+    }
+
+    pub fn name(&self, var_id: VarId) -> Option<&str> {
+        self.names[var_id.id].as_deref()
+    }
+
+    /// The name of the variable, or its `Debug` output if it has none.
+    pub fn describe(&self, var_id: VarId) -> String {
+        match self.name(var_id) {
+            Some(name) => name.to_string(),
+            None => format!("{:?}", var_id),
+        }
+    }
+
+    /// The names of all variables, shared with the solutions read from this
+    /// set.
+    pub(crate) fn names(&self) -> &Arc<Vec<Option<String>>> {
+        &self.names
+    }
+
     pub fn set(&mut self, var_id: VarId, value: &V::Value) {
         let mut new_var = V::with_domain(vec![value]);
         new_var.set_id(var_id);
@@ -256,10 +1088,24 @@ where
         &self.vars[var_id.id]
     }
 
+    /// Gives mutable access to a variable, recording its current domain on
+    /// the trail so the change is undone when the search backtracks.
     pub fn var_mut(&mut self, var_id: VarId) -> &mut V {
+        self.trail.save(var_id.id, &self.vars[var_id.id]);
         &mut self.vars[var_id.id]
     }
 
+    /// Removes `value` from the domain of a variable. Unlike going through
+    /// `var_mut`, the variable is only saved on the trail if it holds the
+    /// value, so propagators can call this on every variable they watch.
+    pub fn remove(&mut self, var_id: VarId, value: &V::Value) -> VarResult<DomainUpdate> {
+        if self.var(var_id).contains(value) {
+            self.var_mut(var_id).remove(value)
+        } else {
+            Ok(DomainUpdate::Unchanged(var_id))
+        }
+    }
+
     pub fn var_id(&self, num: usize) -> VarId {
         self.var_ids[num]
     }
@@ -281,6 +1127,18 @@ where
         true
     }
 
+    pub fn depth(&self) -> usize {
+        self.trail.depth()
+    }
+
+    pub fn push_level(&mut self) {
+        self.trail.push_level();
+    }
+
+    pub fn pop_level(&mut self) {
+        self.trail.pop_level(&mut self.vars);
+    }
+
     pub fn subscribe(&mut self, var_id: VarId, prop_id: PropId) {
         self.subscriptions[var_id.id].push(prop_id);
     }
@@ -289,3 +1147,29 @@ where
         &self.subscriptions[var_id.id]
     }
 }
+
+impl<V> VarSet<V>
+where
+    V: OrderedVariable,
+    V::Value: PartialOrd,
+{
+    /// Removes every value strictly less than `value` from the domain of a
+    /// variable, saving it on the trail only if its lower bound moves.
+    pub fn remove_below(&mut self, var_id: VarId, value: &V::Value) -> VarResult<DomainUpdate> {
+        if self.var(var_id).min() < value {
+            self.var_mut(var_id).remove_below(value)
+        } else {
+            Ok(DomainUpdate::Unchanged(var_id))
+        }
+    }
+
+    /// Removes every value strictly greater than `value` from the domain of
+    /// a variable, saving it on the trail only if its upper bound moves.
+    pub fn remove_above(&mut self, var_id: VarId, value: &V::Value) -> VarResult<DomainUpdate> {
+        if self.var(var_id).max() > value {
+            self.var_mut(var_id).remove_above(value)
+        } else {
+            Ok(DomainUpdate::Unchanged(var_id))
+        }
+    }
+}
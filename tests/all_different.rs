@@ -0,0 +1,162 @@
+extern crate crisp;
+use std::thread;
+
+use crisp::var::{BTreeSetVar, IntervalVar, VarId};
+use crisp::propagate::{AllDifferentConsistency, ValueElimination, DomainConsistency};
+use crisp::propagate::BoundsConsistency;
+use crisp::heuristic::{InputOrder, Random};
+use crisp::Model;
+
+type Var = BTreeSetVar<i32>;
+
+fn domains_model<C>(domains: &[&[i32]], consistency: C) -> (Model<Var>, Vec<VarId>)
+where
+    C: AllDifferentConsistency<Var>,
+{
+    let mut model = Model::<Var>::new();
+    let vars: Vec<_> = domains.iter().map(|&d| model.create_var(d)).collect();
+    model.all_different(&vars, consistency);
+    (model, vars)
+}
+
+#[test]
+fn pigeonhole_at_root() {
+    let mut model = Model::<Var>::new();
+    let vars = model.create_var_array(0..3, 4);
+    model.all_different(&vars, DomainConsistency);
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+    assert_eq!(solver.stats().nodes, 0);
+    assert_eq!(solver.stats().failures, 1);
+}
+
+#[test]
+fn hall_set() {
+    let domains: &[&[i32]] = &[&[1, 2], &[1, 2], &[1, 2, 3, 4], &[2, 3, 4]];
+    let (model, vars) = domains_model(domains, DomainConsistency);
+    let mut solver = model.solve();
+    let solutions: Vec<_> = solver.by_ref().collect();
+    assert_eq!(solutions.len(), 4);
+    for solution in &solutions {
+        assert!(solution[vars[2]] >= 3 && solution[vars[3]] >= 3);
+    }
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn same_solutions() {
+    let domains: &[&[i32]] = &[
+        &[1, 2, 5],
+        &[2, 3],
+        &[1, 3, 4],
+        &[2, 3, 4, 5],
+        &[1, 5],
+        &[0, 2, 4],
+    ];
+    let (value_model, vars) = domains_model(domains, ValueElimination);
+    let (domain_model, _) = domains_model(domains, DomainConsistency);
+    let mut expected: Vec<_> = value_model.solve().map(|s| s.array(&vars)).collect();
+    let mut solver = domain_model.solve();
+    let mut found: Vec<_> = solver.by_ref().map(|s| s.array(&vars)).collect();
+    expected.sort();
+    found.sort();
+    assert!(!found.is_empty());
+    assert_eq!(found, expected);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn backtrack_free_permutations() {
+    let mut model = Model::<Var>::new();
+    let vars = model.create_var_array(0..5, 5);
+    model.all_different(&vars, DomainConsistency);
+    let mut solver = model.solve();
+    assert_eq!(solver.by_ref().count(), 120);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn domain_consistency_on_a_long_chain() {
+    // Each variable takes its own value or the next one, and the next one
+    // of the first variable is taken, which forces all the others along one
+    // long path of the value graph. The search runs on a small stack, which
+    // a recursive walk of that path would overflow.
+    let n = 500;
+    let mut model = Model::<Var>::new();
+    let vars: Vec<_> = (0..n).rev().map(|i| model.create_var([i, i + 1])).collect();
+    let taken = model.create_var([n]);
+    model.all_different(vars.iter().chain(&[taken]), DomainConsistency);
+    let worker = thread::Builder::new().stack_size(64 * 1024).spawn(move || {
+        let mut solver = model.solve();
+        let solution = solver.next().unwrap();
+        assert!(vars.iter().zip((0..n).rev()).all(|(&var, i)| solution[var] == i));
+        assert_eq!(solver.stats().nodes, 0);
+    });
+    worker.unwrap().join().unwrap();
+}
+
+#[test]
+fn bounds_hall_interval_on_wide_domain() {
+    let mut model = Model::<IntervalVar<i64>>::new();
+    let wide = model.create_var(0..1_000_000);
+    let narrow = model.create_var_array(0..3, 3);
+    model.all_different(narrow.iter().chain(&[wide]), BoundsConsistency);
+    let mut solver = model.solve().with_var_selector(InputOrder);
+    let solution = solver.next().unwrap();
+    assert_eq!(solution[wide], 3);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn bounds_pigeonhole_at_root() {
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let vars = model.create_var_array(-5..-2, 4);
+    model.all_different(&vars, BoundsConsistency);
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+    assert_eq!(solver.stats().nodes, 0);
+}
+
+#[test]
+fn bounds_at_the_limits_of_i64() {
+    let mut model = Model::<IntervalVar<i64>>::new();
+    let high = model.create_var_array(i64::MAX - 1..=i64::MAX, 2);
+    let below_high = model.create_var(i64::MAX - 2..=i64::MAX);
+    let low = model.create_var_array(i64::MIN..=i64::MIN + 1, 2);
+    let above_low = model.create_var(i64::MIN..=i64::MIN + 2);
+    let vars = [high[0], high[1], below_high, low[0], low[1], above_low];
+    model.all_different(vars, BoundsConsistency);
+    let mut solver = model.solve();
+    let solutions: Vec<_> = solver.by_ref().map(|s| (s[below_high], s[above_low])).collect();
+    assert_eq!(solutions, vec![(i64::MAX - 2, i64::MIN + 2); 4]);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+/// Solutions of all-different over interval domains given by `ranges`.
+fn interval_solutions<C>(ranges: &[(i32, i32)], consistency: C) -> Vec<Vec<i32>>
+where
+    C: AllDifferentConsistency<IntervalVar<i32>>,
+{
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let vars: Vec<_> = ranges.iter().map(|&(lo, hi)| model.create_var(lo..hi + 1)).collect();
+    model.all_different(&vars, consistency);
+    let mut solutions: Vec<_> = model.solve().map(|s| s.array(&vars)).collect();
+    solutions.sort();
+    solutions
+}
+
+#[test]
+fn random_domains_agree() {
+    let mut random = Random::new(12345);
+    for _ in 0..100 {
+        let ranges: Vec<_> = (0..5)
+            .map(|_| {
+                let lo = random.below(6) as i32;
+                (lo, lo + random.below(4) as i32)
+            })
+            .collect();
+        let expected = interval_solutions(&ranges, ValueElimination);
+        assert_eq!(interval_solutions(&ranges, BoundsConsistency), expected);
+        assert_eq!(interval_solutions(&ranges, DomainConsistency), expected);
+    }
+}
@@ -0,0 +1,96 @@
+extern crate crisp;
+use crisp::var::{BTreeSetVar, IntervalVar};
+use crisp::propagate::Relation;
+use crisp::Model;
+
+fn holds(x: i64, relation: Relation, y: i64) -> bool {
+    match relation {
+        Relation::Eq => x == y,
+        Relation::Ne => x != y,
+        Relation::Lt => x < y,
+        Relation::Le => x <= y,
+        Relation::Gt => x > y,
+        Relation::Ge => x >= y,
+    }
+}
+
+#[test]
+fn matches_brute_force() {
+    let relations = [
+        Relation::Eq,
+        Relation::Ne,
+        Relation::Lt,
+        Relation::Le,
+        Relation::Gt,
+        Relation::Ge,
+    ];
+    let x_domain = [0, 1, 3, 4, 6];
+    let y_domain = [1, 2, 3, 5];
+    for &relation in &relations {
+        for offset in -3..4 {
+            let mut model = Model::<BTreeSetVar<i64>>::new();
+            let x = model.create_var(x_domain);
+            let y = model.create_var(y_domain);
+            model.binary(x, relation, y, offset);
+            let mut found: Vec<_> = model.solve().map(|s| (s[x], s[y])).collect();
+            found.sort();
+
+            let mut expected = vec![];
+            for &a in &x_domain {
+                for &b in &y_domain {
+                    if holds(a, relation, b + offset) {
+                        expected.push((a, b));
+                    }
+                }
+            }
+            assert_eq!(found, expected, "x {:?} y + {}", relation, offset);
+        }
+    }
+}
+
+#[test]
+fn ordering_chain() {
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let vars = model.create_var_array(0..5, 3);
+    model.binary_lt(vars[0], vars[1], 0);
+    model.binary_lt(vars[1], vars[2], 0);
+    model.binary_ge(vars[2], vars[0], 3);
+    let mut solver = model.solve();
+    let mut found: Vec<_> = solver.by_ref().map(|s| s.array(&vars)).collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            vec![0, 1, 3],
+            vec![0, 1, 4],
+            vec![0, 2, 3],
+            vec![0, 2, 4],
+            vec![0, 3, 4],
+            vec![1, 2, 4],
+            vec![1, 3, 4],
+        ]
+    );
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn shifted_equality() {
+    let mut model = Model::<BTreeSetVar<i32>>::new();
+    let x = model.create_var([1, 4, 7, 9]);
+    let y = model.create_var([0, 2, 5, 6]);
+    model.binary_eq(x, y, 2);
+    model.binary_ne(x, y, 5);
+    let found: Vec<_> = model.solve().map(|s| (s[x], s[y])).collect();
+    assert_eq!(found, vec![(4, 2), (7, 5)]);
+}
+
+#[test]
+fn bound_below_value_type() {
+    let mut model = Model::<IntervalVar<u8>>::new();
+    let x = model.create_var(0..10);
+    let y = model.create_var(0..2);
+    model.binary_le(x, y, -3);
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+    assert_eq!(solver.stats().nodes, 0);
+}
@@ -0,0 +1,32 @@
+/// Every assignment of values to variables with the given domains, in
+/// lexicographic order if the domains are sorted.
+pub fn assignments(domains: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    let mut words = vec![vec![]];
+    for domain in domains {
+        words = words
+            .into_iter()
+            .flat_map(|word: Vec<i32>| {
+                domain.iter().map(move |&value| {
+                    let mut word = word.clone();
+                    word.push(value);
+                    word
+                })
+            })
+            .collect();
+    }
+    words
+}
+
+/// The assignments accepted by `accept`, sorted to compare with the sorted
+/// solutions of a model.
+pub fn brute_force<F>(domains: &[Vec<i32>], accept: F) -> Vec<Vec<i32>>
+where
+    F: Fn(&[i32]) -> bool,
+{
+    let mut expected = assignments(domains)
+        .into_iter()
+        .filter(|word| accept(word))
+        .collect::<Vec<_>>();
+    expected.sort();
+    expected
+}
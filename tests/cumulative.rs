@@ -0,0 +1,142 @@
+extern crate crisp;
+use crisp::var::IntervalVar;
+use crisp::propagate::Task;
+use crisp::heuristic::{InputOrder, Random};
+use crisp::Model;
+
+mod common;
+
+/// Whether starting the tasks at `starts` keeps the usage within `capacity`.
+fn fits(tasks: &[(i32, i64, i64)], starts: &[i32], capacity: i64) -> bool {
+    (-5..20).all(|time| {
+        let usage: i64 = tasks
+            .iter()
+            .zip(starts)
+            .filter(|&(&(_, duration, _), &start)| {
+                start <= time && time < start + duration as i32
+            })
+            .map(|(&(_, _, demand), _)| demand)
+            .sum();
+        usage <= capacity
+    })
+}
+
+#[test]
+fn random_tasks_match_brute_force() {
+    let mut random = Random::new(77);
+    for _ in 0..60 {
+        let tasks: Vec<(i32, i64, i64)> = (0..4)
+            .map(|_| {
+                let release = random.below(4) as i32;
+                (release, 1 + random.below(3) as i64, 1 + random.below(3) as i64)
+            })
+            .collect();
+        let capacity = 2 + random.below(2) as i64;
+
+        let mut model = Model::<IntervalVar<i32>>::new();
+        let starts: Vec<_> = tasks
+            .iter()
+            .map(|&(release, _, _)| model.create_var(release..release + 4))
+            .collect();
+        let model_tasks: Vec<_> = tasks
+            .iter()
+            .zip(&starts)
+            .map(|(&(_, duration, demand), &start)| {
+                Task::new(start, duration).with_demand(demand)
+            })
+            .collect();
+        model.cumulative(&model_tasks, capacity);
+        let mut found: Vec<_> = model.solve().map(|s| s.array(&starts)).collect();
+        found.sort();
+
+        let domains: Vec<_> = tasks
+            .iter()
+            .map(|&(release, _, _)| (release..release + 4).collect())
+            .collect();
+        let expected = common::brute_force(&domains, |starts| fits(&tasks, starts, capacity));
+        assert_eq!(found, expected, "{:?} capacity {}", tasks, capacity);
+    }
+}
+
+#[test]
+fn time_tabling_moves_tasks_out_of_compulsory_parts() {
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let fixed = model.create_var(2..3);
+    let free = model.create_var(0..10);
+    model.cumulative(
+        [Task::new(fixed, 3).with_demand(2), Task::new(free, 2)],
+        2,
+    );
+    let mut solver = model.solve();
+    let found: Vec<_> = solver.by_ref().map(|s| s[free]).collect();
+    assert_eq!(found, vec![0, 5, 6, 7, 8, 9]);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn edge_finding_detects_overload_at_root() {
+    // No task has a compulsory part, but the three cannot fit in 0..4.
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let a = model.create_var(0..3);
+    let b = model.create_var(0..3);
+    let c = model.create_var(0..4);
+    model.cumulative(
+        [
+            Task::new(a, 2).with_demand(2),
+            Task::new(b, 2).with_demand(2),
+            Task::new(c, 1).with_demand(2),
+        ],
+        2,
+    );
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+    assert_eq!(solver.stats().nodes, 0);
+}
+
+#[test]
+fn edge_finding_delays_task_after_full_window() {
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let late = model.create_var(0..6);
+    let a = model.create_var(0..3);
+    let b = model.create_var(0..3);
+    model.cumulative(
+        [
+            Task::new(a, 2).with_demand(2),
+            Task::new(b, 2).with_demand(2),
+            Task::new(late, 1).with_demand(2),
+        ],
+        2,
+    );
+    let mut solver = model.solve().with_var_selector(InputOrder);
+    let mut found: Vec<_> = solver.by_ref().map(|s| (s[late], s[a], s[b])).collect();
+    found.sort();
+    assert_eq!(found, vec![(4, 0, 2), (4, 2, 0), (5, 0, 2), (5, 2, 0)]);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn task_above_capacity_fails() {
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let start = model.create_var(0..10);
+    model.cumulative([Task::new(start, 1).with_demand(3)], 2);
+    assert!(model.solve().next().is_none());
+}
+
+#[test]
+fn extreme_times_and_energies_do_not_overflow() {
+    let mut model = Model::<IntervalVar<i64>>::new();
+    let a = model.create_var(i64::MIN..i64::MIN + 4);
+    let b = model.create_var(i64::MIN..i64::MIN + 4);
+    let late = model.create_var(i64::MAX - 4..i64::MAX - 2);
+    let demand = i64::MAX / 2;
+    model.cumulative(
+        [
+            Task::new(a, 2).with_demand(demand),
+            Task::new(b, 2).with_demand(demand),
+            Task::new(late, 2).with_demand(demand),
+        ],
+        demand + 1,
+    );
+    // a and b cannot overlap, which leaves 6 placements for them.
+    assert_eq!(model.solve().count(), 12);
+}
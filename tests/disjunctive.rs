@@ -0,0 +1,88 @@
+extern crate crisp;
+use crisp::var::IntervalVar;
+use crisp::propagate::Task;
+use crisp::heuristic::{InputOrder, Random};
+use crisp::Model;
+
+mod common;
+
+/// Whether no two of the tasks overlap when started at `starts`.
+fn disjoint(durations: &[i32], starts: &[i32]) -> bool {
+    (0..starts.len()).all(|i| {
+        (0..i).all(|j| {
+            starts[i] + durations[i] <= starts[j] || starts[j] + durations[j] <= starts[i]
+        })
+    })
+}
+
+#[test]
+fn random_tasks_match_brute_force() {
+    let mut random = Random::new(4242);
+    for _ in 0..60 {
+        let releases: Vec<i32> = (0..5).map(|_| random.below(6) as i32).collect();
+        let durations: Vec<i32> = (0..5).map(|_| 1 + random.below(3) as i32).collect();
+
+        let mut model = Model::<IntervalVar<i32>>::new();
+        let starts: Vec<_> = releases
+            .iter()
+            .map(|&release| model.create_var(release..release + 4))
+            .collect();
+        let tasks: Vec<_> = starts
+            .iter()
+            .zip(&durations)
+            .map(|(&start, &duration)| Task::new(start, i64::from(duration)))
+            .collect();
+        model.disjunctive(&tasks);
+        let mut found: Vec<_> = model.solve().map(|s| s.array(&starts)).collect();
+        found.sort();
+
+        let domains: Vec<_> = releases
+            .iter()
+            .map(|&release| (release..release + 4).collect())
+            .collect();
+        let expected = common::brute_force(&domains, |starts| disjoint(&durations, starts));
+        assert_eq!(found, expected, "releases {:?} durations {:?}", releases, durations);
+    }
+}
+
+#[test]
+fn overload_fails_at_root() {
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let starts = model.create_var_array(0..3, 3);
+    let tasks: Vec<_> = starts.iter().map(|&start| Task::new(start, 2)).collect();
+    model.disjunctive(&tasks);
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+    assert_eq!(solver.stats().nodes, 0);
+}
+
+#[test]
+fn detectable_precedence_delays_task() {
+    // `first` must run during 1..3, so `second` cannot start before 3.
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let second = model.create_var(0..11);
+    let first = model.create_var(0..2);
+    model.disjunctive([Task::new(first, 3), Task::new(second, 2)]);
+    let mut solver = model.solve().with_var_selector(InputOrder);
+    let mut found: Vec<_> = solver.by_ref().map(|s| (s[first], s[second])).collect();
+    found.sort();
+    let expected: Vec<_> = (0..2)
+        .flat_map(|a| (a + 3..11).map(move |b| (a, b)))
+        .collect();
+    assert_eq!(found, expected);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn not_first_not_last_fail_at_root() {
+    // `long` can neither run before both short tasks nor after them, and
+    // there is no room between them. No single pair shows this.
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let long = model.create_var(2..6);
+    let a = model.create_var(4..7);
+    let b = model.create_var(4..7);
+    model.disjunctive([Task::new(long, 4), Task::new(a, 1), Task::new(b, 1)]);
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+    assert_eq!(solver.stats().nodes, 0);
+}
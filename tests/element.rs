@@ -0,0 +1,73 @@
+extern crate crisp;
+use crisp::var::{BTreeSetVar, HashSetVar, IntervalVar};
+use crisp::Model;
+
+#[test]
+fn element_values_matches_brute_force() {
+    let values = [4, 1, 4, 7, 2];
+    let mut model = Model::<BTreeSetVar<i32>>::new();
+    let index = model.create_var(-1..7);
+    let result = model.create_var(0..6);
+    model.element_values(index, values, result);
+    let mut found: Vec<_> = model.solve().map(|s| (s[index], s[result])).collect();
+    found.sort();
+
+    let expected: Vec<_> = values
+        .iter()
+        .enumerate()
+        .filter(|&(_, &v)| v < 6)
+        .map(|(i, &v)| (i as i32, v))
+        .collect();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn element_vars_matches_brute_force() {
+    let mut model = Model::<HashSetVar<i64>>::new();
+    let index = model.create_var(0..4);
+    let array = model.create_var_array([1, 3, 5], 3);
+    let result = model.create_var([2, 3, 5]);
+    model.element(index, &array, result);
+    let mut found: Vec<_> = model
+        .solve()
+        .map(|s| (s[index], s.array(&array), s[result]))
+        .collect();
+    found.sort();
+
+    let mut expected = vec![];
+    for i in 0..3 {
+        for &a in &[1, 3, 5] {
+            for &b in &[1, 3, 5] {
+                for &c in &[1, 3, 5] {
+                    let chosen = [a, b, c][i as usize];
+                    if [2, 3, 5].contains(&chosen) {
+                        expected.push((i, vec![a, b, c], chosen));
+                    }
+                }
+            }
+        }
+    }
+    expected.sort();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn element_filters_both_ways_before_search() {
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let index = model.create_var(0..10);
+    let result = model.create_var(5..8);
+    model.element_values(index, [1, 6, 3, 7, 9], result);
+    let mut solver = model.solve();
+    let found: Vec<_> = solver.by_ref().map(|s| (s[index], s[result])).collect();
+    assert_eq!(found, vec![(1, 6), (3, 7)]);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn element_without_support_fails() {
+    let mut model = Model::<BTreeSetVar<u8>>::new();
+    let index = model.create_var(0..3);
+    let result = model.create_var([10, 11]);
+    model.element_values(index, [1, 2, 3], result);
+    assert!(model.solve().next().is_none());
+}
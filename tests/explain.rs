@@ -0,0 +1,53 @@
+extern crate crisp;
+use crisp::var::BTreeSetVar;
+use crisp::propagate::ValueElimination;
+use crisp::Model;
+
+type Var = BTreeSetVar<i32>;
+
+#[test]
+fn feasible() {
+    let mut model = Model::<Var>::new();
+    let vars = model.create_var_array(0..3, 3);
+    model.all_different(&vars, ValueElimination);
+    assert_eq!(model.explain_infeasibility(), None);
+}
+
+#[test]
+fn infeasible_domains() {
+    let mut model = Model::<Var>::new();
+    model.create_var(0..3);
+    model.create_var(Vec::<i32>::new());
+    assert_eq!(model.explain_infeasibility(), Some(vec![]));
+
+    let x = model.create_var(0..3);
+    model.linear_eq([(1, x)], 1);
+    assert_eq!(model.explain_infeasibility(), Some(vec![]));
+}
+
+#[test]
+fn bounds_conflict() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_var(0..4);
+    let y = model.create_var(0..4);
+    let z = model.create_var(0..4);
+    model.all_different([y, z], ValueElimination);
+    let x_large = model.linear_ge([(1, x)], 2);
+    model.linear_le([(1, z)], 1);
+    let y_large = model.linear_ge([(1, y)], 2);
+    let sum = model.linear_le([(1, x), (1, y)], 3);
+
+    assert!(model.solve().next().is_none());
+    assert_eq!(model.explain_infeasibility(), Some(vec![x_large, y_large, sum]));
+}
+
+#[test]
+fn conflict_found_by_search() {
+    let mut model = Model::<Var>::new();
+    let vars = model.create_var_array(0..3, 4);
+    model.linear_le([(1, vars[0])], 1);
+    let pigeons = model.all_different(&vars, ValueElimination);
+    model.linear_ne([(1, vars[3])], 0);
+
+    assert_eq!(model.explain_infeasibility(), Some(vec![pigeons]));
+}
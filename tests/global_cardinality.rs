@@ -0,0 +1,106 @@
+extern crate crisp;
+use crisp::var::{BTreeSetVar, IntervalVar};
+use crisp::propagate::Occurrences;
+use crisp::heuristic::Random;
+use crisp::Model;
+
+mod common;
+use common::assignments;
+
+fn count(word: &[i32], value: i32) -> usize {
+    word.iter().filter(|&&v| v == value).count()
+}
+
+#[test]
+fn random_bounds_are_backtrack_free() {
+    let mut random = Random::new(2024);
+    for _ in 0..100 {
+        let domains: Vec<Vec<i32>> = (0..5)
+            .map(|_| (0..5).filter(|_| random.below(3) != 0).collect())
+            .map(|domain: Vec<i32>| if domain.is_empty() { vec![0] } else { domain })
+            .collect();
+        let bounds: Vec<(i32, usize, usize)> = (0..4)
+            .map(|value| {
+                let min = random.below(3);
+                (value, min, min + random.below(3))
+            })
+            .collect();
+
+        let mut model = Model::<BTreeSetVar<i32>>::new();
+        let vars: Vec<_> = domains.iter().map(|d| model.create_var(d)).collect();
+        model.global_cardinality(
+            &vars,
+            bounds.iter().map(|&(v, min, max)| (v, Occurrences::Between(min, max))),
+        );
+        let mut solver = model.solve();
+        let mut found: Vec<_> = solver.by_ref().map(|s| s.array(&vars)).collect();
+        found.sort();
+
+        let expected = common::brute_force(&domains, |word| {
+            bounds.iter().all(|&(v, min, max)| (min..max + 1).contains(&count(word, v)))
+        });
+        assert_eq!(found, expected, "{:?} {:?}", domains, bounds);
+        if !expected.is_empty() {
+            assert_eq!(solver.stats().failures, 0, "{:?} {:?}", domains, bounds);
+        }
+    }
+}
+
+#[test]
+fn count_vars_match_brute_force() {
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let people = model.create_var_array(0..3, 4);
+    let counts = model.create_var_array(0..3, 3);
+    model.set(counts[2], &2);
+    model.global_cardinality(
+        &people,
+        (0..3).map(|shift| (shift, Occurrences::Var(counts[shift as usize]))),
+    );
+    let mut found: Vec<_> = model
+        .solve()
+        .map(|s| (s.array(&people), s.array(&counts)))
+        .collect();
+    found.sort();
+
+    let mut expected = vec![];
+    for word in assignments(&vec![vec![0, 1, 2]; 4]) {
+        let shifts: Vec<_> = (0..3).map(|shift| count(&word, shift) as i32).collect();
+        if shifts[0] <= 2 && shifts[1] <= 2 && shifts[2] == 2 {
+            expected.push((word, shifts));
+        }
+    }
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn exact_staffing_fills_every_shift() {
+    let mut model = Model::<BTreeSetVar<i32>>::new();
+    let people = model.create_var_array(0..3, 5);
+    model.set(people[0], &1);
+    model.set(people[1], &1);
+    model.global_cardinality(
+        &people,
+        vec![
+            (0, Occurrences::exactly(2)),
+            (1, Occurrences::exactly(2)),
+            (2, Occurrences::exactly(1)),
+        ],
+    );
+    let mut solver = model.solve();
+    let found = solver.by_ref().count();
+    assert_eq!(found, 3);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn too_many_required_occurrences_fail_at_root() {
+    let mut model = Model::<BTreeSetVar<i32>>::new();
+    let people = model.create_var_array(0..2, 3);
+    model.global_cardinality(
+        &people,
+        vec![(0, Occurrences::exactly(2)), (1, Occurrences::exactly(2))],
+    );
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+    assert_eq!(solver.stats().nodes, 0);
+}
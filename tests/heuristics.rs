@@ -0,0 +1,166 @@
+extern crate crisp;
+use crisp::var::{BTreeSetVar, Variable, VarId};
+use crisp::heuristic::{InputOrder, FirstFail, MostConstrained, DomOverDeg, LargestDomain, Random};
+use crisp::heuristic::{MinValue, MaxValue, MedianValue, SplitLowerHalf};
+use crisp::heuristic::{DWayBranching, Bisection};
+use crisp::var::IntervalVar;
+use crisp::solve::Solver;
+use crisp::propagate::ValueElimination;
+use crisp::Model;
+
+type Var = BTreeSetVar<i32>;
+
+fn build_model() -> (Model<Var>, Vec<VarId>) {
+    let mut model = Model::<Var>::new();
+    let mut vars = model.create_var_array(0..6, 4);
+    vars.push(model.create_var(0..3));
+    model.all_different(&vars[..4], ValueElimination);
+    model.linear_le(vars.iter().map(|&x| (1, x)), 9);
+    model.linear_ne([(1, vars[0]), (-1, vars[4])], 0);
+    (model, vars)
+}
+
+fn solutions(solver: Solver<Var>, vars: &[VarId]) -> Vec<Vec<i32>> {
+    let mut solutions = solver
+        .map(|s| vars.iter().map(|&x| s[x]).collect())
+        .collect::<Vec<_>>();
+    solutions.sort();
+    solutions
+}
+
+#[test]
+fn selectors_agree() {
+    let (model, vars) = build_model();
+    let expected = solutions(model.solve(), &vars);
+    assert!(!expected.is_empty());
+
+    let solvers = vec![
+        model.solve().with_var_selector(InputOrder),
+        model.solve().with_var_selector(FirstFail),
+        model.solve().with_var_selector(MostConstrained),
+        model.solve().with_var_selector(DomOverDeg),
+        model.solve().with_var_selector(LargestDomain),
+        model.solve().with_var_selector(Random::new(7)),
+    ];
+    for solver in solvers {
+        assert_eq!(solutions(solver, &vars), expected);
+    }
+}
+
+#[test]
+fn empty_domain_fails() {
+    let mut model = Model::<Var>::new();
+    let vars = model.create_var_array(0..3, 2);
+    let empty = model.create_var(Vec::<i32>::new());
+    model.linear_ne([(1, vars[0]), (-1, vars[1])], 0);
+
+    let solvers = vec![
+        model.solve().with_var_selector(InputOrder),
+        model.solve().with_var_selector(FirstFail),
+        model.solve().with_var_selector(MostConstrained),
+        model.solve().with_var_selector(DomOverDeg),
+        model.solve().with_var_selector(LargestDomain),
+        model.solve().with_var_selector(Random::new(7)),
+    ];
+    for mut solver in solvers {
+        assert!(solver.next().is_none());
+        assert_eq!(solver.stats().failures, 1);
+        assert_eq!(solver.last_failure().and_then(|failure| failure.var_id), Some(empty));
+    }
+}
+
+#[test]
+fn random_is_reproducible() {
+    let (model, vars) = build_model();
+    let first = |seed| {
+        let solution = model
+            .solve()
+            .with_var_selector(Random::new(seed))
+            .next()
+            .unwrap();
+        vars.iter()
+            .map(|&x| solution[x])
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(first(3), first(3));
+}
+
+#[test]
+fn random_any_seed() {
+    for &seed in &[0, 0x9e37_79b9_7f4a_7c15, u64::MAX] {
+        let mut random = Random::new(seed);
+        let draws: Vec<_> = (0..20).map(|_| random.below(1000)).collect();
+        assert!(draws.iter().any(|&draw| draw != draws[0]), "seed {:#x}", seed);
+    }
+}
+
+#[test]
+fn value_selectors_agree() {
+    let (model, vars) = build_model();
+    let expected = solutions(model.solve(), &vars);
+
+    let solvers = vec![
+        model.solve().with_value_selector(MinValue),
+        model.solve().with_value_selector(MaxValue),
+        model.solve().with_value_selector(MedianValue),
+        model.solve().with_value_selector(Random::new(11)),
+        model.solve().with_value_selector(SplitLowerHalf),
+        model.solve().with_value_selector(|var: &Var| var.possibilities().nth(1).unwrap()),
+    ];
+    for solver in solvers {
+        assert_eq!(solutions(solver, &vars), expected);
+    }
+}
+
+#[test]
+fn value_selector_groups() {
+    let (model, vars) = build_model();
+    let first = model
+        .solve()
+        .with_var_selector(InputOrder)
+        .with_value_selector(MaxValue)
+        .with_value_selector_for(&vars[..2], MinValue)
+        .next()
+        .unwrap();
+    let values = vars.iter()
+        .map(|&x| first[x])
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec![0, 1, 5, 2, 1]);
+}
+
+#[test]
+fn branchings_agree() {
+    let (model, vars) = build_model();
+    let expected = solutions(model.solve(), &vars);
+
+    assert_eq!(solutions(model.solve().with_branching(DWayBranching), &vars), expected);
+    assert_eq!(solutions(model.solve().with_branching(Bisection), &vars), expected);
+    assert_eq!(
+        solutions(
+            model
+                .solve()
+                .with_branching(Bisection)
+                .with_value_selector(MaxValue),
+            &vars,
+        ),
+        expected
+    );
+}
+
+#[test]
+fn bisection_large_domain() {
+    let mut model = Model::<IntervalVar<i64>>::new();
+    let x = model.create_var(0..1_000_000);
+    let y = model.create_var(0..1_000_000);
+    model.linear_eq([(1, x), (1, y)], 999_999);
+    model.linear_ne([(1, x), (-1, y)], 1);
+
+    let solution = model
+        .solve()
+        .with_branching(Bisection)
+        .with_value_selector(MaxValue)
+        .next()
+        .unwrap();
+    assert_eq!(solution[x], 999_999);
+    assert_eq!(solution[y], 0);
+}
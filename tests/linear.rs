@@ -0,0 +1,66 @@
+extern crate crisp;
+use crisp::var::{BTreeSetVar, IntervalVar};
+use crisp::propagate::Relation;
+use crisp::propagate::ValueElimination;
+use crisp::Model;
+
+#[test]
+fn send_more_money() {
+    let mut model = Model::<BTreeSetVar<i32>>::new();
+    let letters = model.create_var_array(0..10, 8);
+    let (s, e, n, d, m, o, r, y) = (
+        letters[0],
+        letters[1],
+        letters[2],
+        letters[3],
+        letters[4],
+        letters[5],
+        letters[6],
+        letters[7],
+    );
+    model.all_different(&letters, ValueElimination);
+    model.linear_ne([(1, s)], 0);
+    model.linear_ne([(1, m)], 0);
+    model.linear_eq(
+        [
+            (1000, s),
+            (100, e),
+            (10, n),
+            (1, d),
+            (1000, m),
+            (100, o),
+            (10, r),
+            (1, e),
+            (-10000, m),
+            (-1000, o),
+            (-100, n),
+            (-10, e),
+            (-1, y),
+        ],
+        0,
+    );
+
+    let solutions = model.solve().collect::<Vec<_>>();
+    assert_eq!(solutions.len(), 1);
+    let values = letters
+        .iter()
+        .map(|&id| solutions[0][id])
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec![9, 5, 6, 7, 1, 0, 8, 2]);
+}
+
+#[test]
+fn bounds_filtering() {
+    let mut model = Model::<IntervalVar<i64>>::new();
+    let x = model.create_var(0..1_000_000);
+    let y = model.create_var(0..1_000_000);
+    model.linear([(3, x), (2, y)], Relation::Le, 12);
+    model.linear([(1, x), (-1, y)], Relation::Gt, 0);
+    model.linear_ge([(1, y)], 1);
+
+    let solutions = model
+        .solve()
+        .map(|s| (s[x], s[y]))
+        .collect::<Vec<_>>();
+    assert_eq!(solutions, vec![(2, 1), (3, 1)]);
+}
@@ -0,0 +1,75 @@
+extern crate crisp;
+use crisp::var::BTreeSetVar;
+use crisp::propagate::ValueElimination;
+use crisp::Model;
+
+type Var = BTreeSetVar<i32>;
+
+#[test]
+fn named_vars() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_named_var("x", 0..3);
+    let ys = model.create_named_var_array("y", 0..3, 2);
+    let zs = model.create_named_var_matrix("z", 0..3, 2, 2);
+    let w = model.create_var(0..3);
+    assert_eq!(model.var_name(x), Some("x"));
+    assert_eq!(model.var_name(ys[1]), Some("y[1]"));
+    assert_eq!(model.var_name(zs[1][0]), Some("z[1][0]"));
+    assert_eq!(model.var_name(w), None);
+
+    model.name_var(w, "w");
+    assert_eq!(model.var_name(w), Some("w"));
+}
+
+#[test]
+fn solution_names() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_named_var("x", 1..2);
+    let ys = model.create_named_var_array("y", 2..3, 2);
+    let z = model.create_var(0..1);
+    let solution = model.solve().next().unwrap();
+
+    assert_eq!(solution.name(ys[0]), Some("y[0]"));
+    assert_eq!(solution.name(z), None);
+    assert_eq!(solution.by_name("x"), Some(&1));
+    assert_eq!(solution.by_name("y[1]"), Some(&2));
+    assert_eq!(solution.by_name("v"), None);
+    assert_eq!(
+        format!("{:?}", solution),
+        format!("{{x: 1, y[0]: 2, y[1]: 2, {:?}: 0}}", z)
+    );
+    assert_eq!(
+        solution.to_string(),
+        format!("x = 1\ny[0] = 2\ny[1] = 2\n{:?} = 0\n", z)
+    );
+    assert_eq!(solution[x], 1);
+}
+
+#[test]
+fn labelled_failure() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_named_var("x", 0..3);
+    let y = model.create_named_var("y", 0..3);
+    let different = model.all_different([x, y], ValueElimination);
+    model.label(different, "x != y");
+    let x_one = model.linear_eq([(1, x)], 1);
+    model.label(x_one, "x = 1");
+    let y_one = model.linear_eq([(1, y)], 1);
+    assert_eq!(model.prop_label(different), Some("x != y"));
+    assert_eq!(model.prop_label(y_one), None);
+
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+    let failure = solver.last_failure().unwrap();
+    assert_eq!(solver.describe_failure(&failure), "domain of y wiped out by x != y");
+
+    let report = solver.stats_report();
+    assert!(report.contains("failures: 1\n"));
+    assert!(report.contains("    x != y: 3\n"));
+    assert!(report.contains("    x = 1: 2\n"));
+    assert!(report.contains(&format!("    {:?}: 1\n", y_one)));
+
+    let conflict = model.explain_infeasibility().unwrap();
+    let labels: Vec<_> = conflict.iter().map(|&p| model.prop_label(p)).collect();
+    assert_eq!(labels, vec![Some("x != y"), Some("x = 1"), None]);
+}
@@ -0,0 +1,45 @@
+extern crate crisp;
+use crisp::var::IntervalVar;
+use crisp::Model;
+
+type Var = IntervalVar<i64>;
+
+#[test]
+fn knapsack() {
+    let weights = [12, 2, 1, 4, 1];
+    let values = [4, 2, 1, 10, 2];
+
+    let mut model = Model::<Var>::new();
+    let take = model.create_var_array(0..2, 5);
+    let value = model.create_var(0..100);
+    model.linear_le(weights.iter().zip(&take).map(|(&w, &x)| (w, x)), 15);
+    let mut terms = values
+        .iter()
+        .zip(&take)
+        .map(|(&v, &x)| (v, x))
+        .collect::<Vec<_>>();
+    terms.push((-1, value));
+    model.linear_eq(terms, 0);
+
+    let mut solver = model.maximize(value);
+    let mut objectives = vec![];
+    while let Some(solution) = solver.next() {
+        objectives.push(solution[value]);
+        assert!(!solver.optimality_proven());
+    }
+    assert!(solver.optimality_proven());
+    assert_eq!(solver.best_objective(), Some(&15));
+    assert_eq!(objectives.last(), Some(&15));
+    assert!(objectives.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn minimize_infeasible() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_var(0..10);
+    model.linear_ge([(1, x)], 20);
+
+    let mut solver = model.minimize(x);
+    assert!(solver.next().is_none());
+    assert!(!solver.optimality_proven());
+}
@@ -0,0 +1,69 @@
+extern crate crisp;
+use crisp::var::{BTreeSetVar, HashSetVar};
+use crisp::propagate::Automaton;
+use crisp::Model;
+
+/// Days (0) and nights (1) with at most `limit` nights in a row. State `s`
+/// counts the nights worked since the last day.
+fn night_limit(limit: usize) -> Automaton<i32> {
+    let mut transitions = vec![];
+    for state in 0..limit + 1 {
+        transitions.push((state, 0, 0));
+        if state < limit {
+            transitions.push((state, 1, state + 1));
+        }
+    }
+    Automaton::new(limit + 1, 0, transitions, 0..limit + 1)
+}
+
+#[test]
+fn night_limit_matches_brute_force() {
+    let automaton = night_limit(2);
+    let mut model = Model::<BTreeSetVar<i32>>::new();
+    let shifts = model.create_var_array(0..2, 7);
+    model.regular(&shifts, automaton.clone());
+    let mut found: Vec<_> = model.solve().map(|s| s.array(&shifts)).collect();
+    found.sort();
+
+    let mut expected = vec![];
+    for word in 0..1 << 7 {
+        let word: Vec<i32> = (0..7).map(|i| (word >> (6 - i)) & 1).collect();
+        let too_many = word.windows(3).any(|w| w == [1, 1, 1]);
+        assert_eq!(automaton.accepts(&word), !too_many);
+        if !too_many {
+            expected.push(word);
+        }
+    }
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn regular_filters_before_search() {
+    // Words over {0, 1, 2} containing at least one 2.
+    let transitions = [(0, 0, 0), (0, 1, 0), (0, 2, 1), (1, 0, 1), (1, 1, 1), (1, 2, 1)];
+    let automaton = Automaton::new(2, 0, transitions, [1]);
+    let mut model = Model::<HashSetVar<i32>>::new();
+    let vars = model.create_var_array(0..3, 4);
+    for &var_id in &vars[..3] {
+        model.set(var_id, &0);
+    }
+    model.regular(&vars, automaton);
+    let mut solver = model.solve();
+    let found: Vec<_> = solver.by_ref().map(|s| s.array(&vars)).collect();
+    assert_eq!(found, vec![vec![0, 0, 0, 2]]);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn regular_without_accepted_word_fails() {
+    let mut model = Model::<BTreeSetVar<i32>>::new();
+    let shifts = model.create_var_array([1], 3);
+    model.regular(&shifts, night_limit(2));
+    assert!(model.solve().next().is_none());
+}
+
+#[test]
+#[should_panic(expected = "not deterministic")]
+fn nondeterministic_automaton_panics() {
+    Automaton::new(2, 0, [(0, 1, 0), (0, 1, 1)], [1]);
+}
@@ -0,0 +1,203 @@
+extern crate crisp;
+use std::thread;
+use std::time::Duration;
+
+use crisp::var::{BTreeSetVar, Failure};
+use crisp::solve::{CancelToken, Interrupted, SearchLimits, SearchStatus, Limit, Solver};
+use crisp::propagate::ValueElimination;
+use crisp::Model;
+
+type Var = BTreeSetVar<i32>;
+
+/// All permutations of 0..n.
+fn permutations(n: i32) -> Model<Var> {
+    let mut model = Model::<Var>::new();
+    let vars = model.create_var_array(0..n, n as usize);
+    model.all_different(&vars, ValueElimination);
+    model
+}
+
+fn limited(limits: SearchLimits) -> Solver<Var> {
+    permutations(6).solve().with_limits(limits)
+}
+
+#[test]
+fn exhausted() {
+    let mut solver = permutations(4).solve();
+    assert_eq!(solver.status(), SearchStatus::Running);
+    assert_eq!(solver.by_ref().count(), 24);
+    assert_eq!(solver.status(), SearchStatus::Exhausted);
+}
+
+#[test]
+fn infeasible() {
+    let mut model = Model::<Var>::new();
+    let vars = model.create_var_array(0..3, 4);
+    model.all_different(&vars, ValueElimination);
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+    assert_eq!(solver.status(), SearchStatus::Exhausted);
+}
+
+#[test]
+fn solution_limit() {
+    let mut solver = limited(SearchLimits {
+        max_solutions: Some(5),
+        ..SearchLimits::default()
+    });
+    assert_eq!(solver.by_ref().count(), 5);
+    assert_eq!(solver.status(), SearchStatus::LimitReached(Limit::Solutions));
+}
+
+#[test]
+fn node_limit() {
+    let mut solver = limited(SearchLimits {
+        max_nodes: Some(10),
+        ..SearchLimits::default()
+    });
+    assert!(solver.by_ref().count() < 720);
+    assert_eq!(solver.status(), SearchStatus::LimitReached(Limit::Nodes));
+}
+
+#[test]
+fn failure_limit() {
+    let mut model = Model::<Var>::new();
+    let vars = model.create_var_array(0..6, 7);
+    model.all_different(&vars, ValueElimination);
+    let mut solver = model.solve().with_limits(SearchLimits {
+        max_failures: Some(3),
+        ..SearchLimits::default()
+    });
+    assert!(solver.next().is_none());
+    assert_eq!(solver.status(), SearchStatus::LimitReached(Limit::Failures));
+}
+
+#[test]
+fn time_limit() {
+    let mut solver = limited(SearchLimits {
+        time_limit: Some(Duration::from_secs(0)),
+        ..SearchLimits::default()
+    });
+    assert_eq!(solver.by_ref().count(), 0);
+    assert_eq!(solver.status(), SearchStatus::LimitReached(Limit::Time));
+}
+
+#[test]
+fn stats() {
+    let mut solver = permutations(4).solve();
+    assert_eq!(solver.stats().nodes, 0);
+    assert_eq!(solver.by_ref().count(), 24);
+
+    let stats = solver.stats();
+    assert_eq!(stats.solutions, 24);
+    // Every node is undone once the tree is exhausted.
+    assert_eq!(stats.backtracks, stats.nodes);
+    assert!(stats.max_depth >= 3);
+    assert_eq!(stats.propagations.len(), 1);
+    assert!(stats.total_propagations() > stats.nodes);
+}
+
+#[test]
+fn infeasible_stats() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_var(0..3);
+    let y = model.create_var(0..3);
+    model.all_different([x, y], ValueElimination);
+    model.linear_eq([(1, x), (-1, y)], 0);
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+
+    let stats = solver.stats();
+    assert_eq!(stats.solutions, 0);
+    assert!(stats.failures > 0);
+    assert_eq!(stats.backtracks, stats.nodes);
+    assert_eq!(stats.propagations.len(), 2);
+}
+
+#[test]
+fn cancelled_before_start() {
+    let mut solver = permutations(4).solve();
+    solver.cancel_token().cancel();
+    assert_eq!(solver.try_next().err(), Some(Interrupted));
+    assert_eq!(solver.status(), SearchStatus::Interrupted);
+    assert!(solver.next().is_none());
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn cancelled_between_solutions() {
+    let token = CancelToken::new();
+    let mut solver = permutations(4).solve().with_cancel_token(token.clone());
+    assert_eq!(solver.by_ref().take(3).count(), 3);
+    assert!(solver.try_next().unwrap().is_some());
+    token.cancel();
+    assert_eq!(solver.try_next().err(), Some(Interrupted));
+    assert_eq!(solver.stats().solutions, 4);
+}
+
+#[test]
+fn cancelled_from_another_thread() {
+    let token = CancelToken::new();
+    let worker_token = token.clone();
+    let worker = thread::spawn(move || {
+        let mut solver = permutations(12).solve().with_cancel_token(worker_token);
+        let count = solver.by_ref().count();
+        (count, solver.status())
+    });
+    thread::sleep(Duration::from_millis(50));
+    token.cancel();
+    let (count, status) = worker.join().unwrap();
+    assert!(count < 479_001_600);
+    assert_eq!(status, SearchStatus::Interrupted);
+}
+
+#[test]
+fn solver_moved_to_another_thread() {
+    let mut solver = permutations(12).solve();
+    let token = solver.cancel_token();
+    assert!(solver.next().is_some());
+    let worker = thread::spawn(move || {
+        let count = solver.by_ref().count();
+        (count, solver.status())
+    });
+    thread::sleep(Duration::from_millis(50));
+    token.cancel();
+    let (count, status) = worker.join().unwrap();
+    assert!(count < 479_001_600);
+    assert_eq!(status, SearchStatus::Interrupted);
+}
+
+#[test]
+fn infeasible_failure() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_var(0..3);
+    let y = model.create_var(0..3);
+    let prop_id = model.linear_le([(1, x), (1, y)], -1);
+    let mut solver = model.solve();
+    assert!(solver.last_failure().is_none());
+    assert!(solver.next().is_none());
+
+    let failure = solver.last_failure().unwrap();
+    assert_eq!(failure.var_id, None);
+    assert_eq!(failure.prop_id, Some(prop_id));
+    assert_eq!(failure.reason, Some("minimum of the sum exceeds the bound"));
+    assert_eq!(
+        failure.to_string(),
+        format!("inconsistency by {:?}: minimum of the sum exceeds the bound", prop_id)
+    );
+}
+
+#[test]
+fn wipe_out_failure() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_var(0..3);
+    let y = model.create_var(0..3);
+    let all_different = model.all_different([x, y], ValueElimination);
+    model.linear_eq([(1, x)], 1);
+    model.linear_eq([(1, y)], 1);
+    let mut solver = model.solve();
+    assert!(solver.next().is_none());
+
+    let failure = solver.last_failure().unwrap();
+    assert_eq!(failure, Failure::wipe_out(y).with_prop(all_different));
+}
@@ -0,0 +1,62 @@
+extern crate crisp;
+use std::collections::HashMap;
+
+use crisp::var::BTreeSetVar;
+use crisp::propagate::ValueElimination;
+use crisp::Model;
+
+type Var = BTreeSetVar<i32>;
+
+#[test]
+fn lookup() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_var(0..10);
+    let y = model.create_var(0..10);
+    model.linear_eq([(1, x)], 3);
+    model.linear_eq([(1, x), (1, y)], 10);
+    let solution = model.solve().next().unwrap();
+
+    assert_eq!(solution[x], 3);
+    assert_eq!(solution.get(y), Some(&7));
+    assert_eq!(solution.len(), 2);
+    assert_eq!(solution.iter().collect::<Vec<_>>(), vec![(x, &3), (y, &7)]);
+    assert_eq!(format!("{:?}", solution), format!("{{{:?}: 3, {:?}: 7}}", x, y));
+
+    let mut expected = HashMap::new();
+    expected.insert(x, 3);
+    expected.insert(y, 7);
+    assert_eq!(solution.to_hash_map(), expected);
+    assert_eq!(HashMap::from(solution), expected);
+}
+
+#[test]
+fn empty_domain() {
+    let mut model = Model::<Var>::new();
+    model.create_var(0..3);
+    model.create_var(Vec::<i32>::new());
+    assert!(model.solve().next().is_none());
+}
+
+#[test]
+fn unknown_var() {
+    let mut bigger = Model::<Var>::new();
+    bigger.create_var_array(0..1, 2);
+    let mut model = Model::<Var>::new();
+    model.create_var(0..1);
+    let other = bigger.solve().next().unwrap();
+    let last = other.iter().last().unwrap().0;
+    assert_eq!(model.solve().next().unwrap().get(last), None);
+}
+
+#[test]
+fn arrays_and_matrices() {
+    let mut model = Model::<Var>::new();
+    let array = model.create_var_array(0..3, 3);
+    let matrix = model.create_var_matrix(0..4, 2, 2);
+    model.all_different(&array, ValueElimination);
+    model.all_different(matrix.iter().flatten(), ValueElimination);
+    let solution = model.solve().next().unwrap();
+
+    assert_eq!(solution.array(&array), vec![0, 1, 2]);
+    assert_eq!(solution.matrix(&matrix), vec![vec![0, 1], vec![2, 3]]);
+}
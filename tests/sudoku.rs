@@ -1,31 +1,38 @@
+#![allow(clippy::needless_range_loop)]
+
 #[macro_use]
 extern crate itertools;
 
 extern crate crisp;
-use crisp::var::{BTreeSetVar, Variable, VarSet, VarId};
+use crisp::var::{BTreeSetVar, VarId};
+use crisp::solution::Solution;
+use crisp::propagate::{AllDifferentConsistency, ValueElimination, DomainConsistency};
 use crisp::Model;
 
 type Value = u8;
 type Var = BTreeSetVar<u8>;
 type Board = [[Value; 9]; 9];
 
-fn build_model(board: &Board) -> (Model<Var>, Vec<Vec<VarId>>) {
+fn build_model<C>(board: &Board, consistency: C) -> (Model<Var>, Vec<Vec<VarId>>)
+where
+    C: AllDifferentConsistency<Var> + Copy,
+{
     let mut model = Model::<Var>::new();
-    let var_id_matrix = model.create_var_matrix(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 9, 9);
+    let var_id_matrix = model.create_var_matrix([1, 2, 3, 4, 5, 6, 7, 8, 9], 9, 9);
 
     for r in 0..9 {
         let row = &var_id_matrix[r];
-        model.all_different(row);
+        model.all_different(row, consistency);
     }
 
     for c in 0..9 {
         let col = var_id_matrix.iter().map(|row| row[c]);
-        model.all_different(col);
+        model.all_different(col, consistency);
     }
 
     for (&r, &c) in iproduct!(&[0, 3, 6], &[0, 3, 6]) {
         let block = (0..9).map(|n| var_id_matrix[r + n / 3][c + n % 3]);
-        model.all_different(block);
+        model.all_different(block, consistency);
     }
 
     for r in 0..9 {
@@ -39,21 +46,30 @@ fn build_model(board: &Board) -> (Model<Var>, Vec<Vec<VarId>>) {
     (model, var_id_matrix)
 }
 
-fn verify_solution(var_ids: &Vec<Vec<VarId>>, solution: &VarSet<Var>, expected: &Board) {
+fn verify_solution(var_ids: &[Vec<VarId>], solution: &Solution<Var>, expected: &Board) {
+    let values = solution.matrix(var_ids);
     for r in 0..9 {
-        for c in 0..9 {
-            assert_eq!(solution.var(var_ids[r][c]).value().unwrap(), &expected[r][c]);
-        }
+        assert_eq!(values[r][..], expected[r][..]);
     }
 }
 
-fn test_sudoku(board: &Board, expected: &Board) {
-    let (model, var_id_matrix) = build_model(&board);
+fn solve_sudoku<C>(board: &Board, expected: &Board, consistency: C) -> u64
+where
+    C: AllDifferentConsistency<Var> + Copy,
+{
+    let (model, var_id_matrix) = build_model(board, consistency);
     let mut solver = model.solve();
     let solution = solver.next();
     assert!(solution.is_some());
     assert!(solver.next().is_none());
-    verify_solution(&var_id_matrix, &solution.unwrap(), &expected);
+    verify_solution(&var_id_matrix, &solution.unwrap(), expected);
+    solver.stats().nodes
+}
+
+fn test_sudoku(board: &Board, expected: &Board) {
+    let value_nodes = solve_sudoku(board, expected, ValueElimination);
+    let domain_nodes = solve_sudoku(board, expected, DomainConsistency);
+    assert!(domain_nodes <= value_nodes);
 }
 
 #[test]
@@ -0,0 +1,104 @@
+extern crate crisp;
+use crisp::var::{BTreeSetVar, HashSetVar};
+use crisp::propagate::ValueElimination;
+use crisp::heuristic::Random;
+use crisp::Model;
+
+mod common;
+
+type Var = BTreeSetVar<i32>;
+
+/// Pseudo-random tuples of length 3 over 0..4.
+fn random_tuples(count: usize, seed: u64) -> Vec<Vec<i32>> {
+    let mut random = Random::new(seed);
+    (0..count)
+        .map(|_| (0..3).map(|_| random.below(4) as i32).collect())
+        .collect()
+}
+
+#[test]
+fn allowed_tuples() {
+    for seed in 0..20 {
+        let tuples = random_tuples(10, seed);
+        let mut model = Model::<Var>::new();
+        let vars = model.create_var_array(0..4, 3);
+        model.table(&vars, &tuples);
+        let mut solver = model.solve();
+        let mut found: Vec<_> = solver.by_ref().map(|s| s.array(&vars)).collect();
+        found.sort();
+
+        let mut expected = tuples.clone();
+        expected.sort();
+        expected.dedup();
+        assert_eq!(found, expected);
+        // A single generalized arc consistent constraint never fails.
+        assert_eq!(solver.stats().failures, 0);
+    }
+}
+
+#[test]
+fn forbidden_tuples() {
+    for seed in 0..20 {
+        let mut tuples = random_tuples(40, seed);
+        tuples.extend(random_tuples(5, seed));
+        let mut model = Model::<Var>::new();
+        let vars = model.create_var_array(0..4, 3);
+        model.forbidden_table(&vars, &tuples);
+        let mut found: Vec<_> = model.solve().map(|s| s.array(&vars)).collect();
+        found.sort();
+
+        let expected = common::brute_force(&vec![(0..4).collect(); 3], |tuple| {
+            !tuples.iter().any(|forbidden| forbidden[..] == *tuple)
+        });
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn forbidden_prunes_at_root() {
+    let mut model = Model::<Var>::new();
+    let x = model.create_var(0..3);
+    let y = model.create_var(0..2);
+    model.forbidden_table([x, y], [[2, 0], [2, 1], [0, 0]]);
+    let mut solver = model.solve();
+    let found: Vec<_> = solver.by_ref().map(|s| (s[x], s[y])).collect();
+    assert_eq!(found, vec![(0, 1), (1, 0), (1, 1)]);
+    assert_eq!(solver.stats().failures, 0);
+}
+
+#[test]
+fn compatibility_rules() {
+    let mut model = Model::<HashSetVar<&str>>::new();
+    let engine = model.create_named_var("engine", ["petrol", "diesel", "electric"]);
+    let gearbox = model.create_named_var("gearbox", ["manual", "automatic"]);
+    let fuel = model.create_named_var("fuel", ["95", "diesel", "none"]);
+    model.table(
+        [engine, gearbox],
+        [
+            ["petrol", "manual"],
+            ["petrol", "automatic"],
+            ["diesel", "manual"],
+            ["electric", "automatic"],
+        ],
+    );
+    model.table(
+        [engine, fuel],
+        [["petrol", "95"], ["diesel", "diesel"], ["electric", "none"]],
+    );
+    model.table([gearbox], [["automatic"]]);
+    let mut found: Vec<_> = model.solve().map(|s| (s[engine], s[fuel])).collect();
+    found.sort();
+    assert_eq!(found, vec![("electric", "none"), ("petrol", "95")]);
+}
+
+#[test]
+fn with_other_constraints() {
+    let mut model = Model::<Var>::new();
+    let vars = model.create_var_array(0..4, 3);
+    let tuples = common::assignments(&vec![(0..4).collect(); 3]);
+    model.table(&vars, tuples.into_iter().filter(|t| t[0] + t[1] == t[2]));
+    model.all_different(&vars, ValueElimination);
+    let mut found: Vec<_> = model.solve().map(|s| s.array(&vars)).collect();
+    found.sort();
+    assert_eq!(found, vec![vec![1, 2, 3], vec![2, 1, 3]]);
+}
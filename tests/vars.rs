@@ -0,0 +1,146 @@
+extern crate crisp;
+use crisp::var::{
+    BTreeSetVar, BitSetVar, DomainUpdate, Failure, IntervalVar, OrderedVariable, Variable,
+};
+use crisp::propagate::ValueElimination;
+use crisp::Model;
+
+#[test]
+fn interval_wide_domain() {
+    let mut var = IntervalVar::<i64>::with_domain(0..1_000_000);
+    assert_eq!(var.size(), 1_000_000);
+    assert!(var.holes().is_empty());
+
+    var.remove(&0).unwrap();
+    var.remove(&999_999).unwrap();
+    var.remove(&500).unwrap();
+    assert_eq!(var.size(), 999_997);
+    assert!(!var.contains(&500));
+    assert!(var.contains(&501));
+    assert_eq!(var.possibilities().take(3).collect::<Vec<_>>(), vec![1, 2, 3]);
+}
+
+#[test]
+fn interval_holes() {
+    let mut var = IntervalVar::<u8>::with_domain([1, 2, 4, 7]);
+    assert_eq!(var.size(), 4);
+    assert_eq!(var.possibilities().collect::<Vec<_>>(), vec![1, 2, 4, 7]);
+
+    var.remove(&1).unwrap();
+    var.remove(&2).unwrap();
+    assert_eq!(var.possibilities().collect::<Vec<_>>(), vec![4, 7]);
+    assert_eq!(var.holes().len(), 1);
+
+    var.remove(&7).unwrap();
+    assert_eq!(var.value(), Some(&4));
+    assert!(var.holes().is_empty());
+    assert_eq!(var.remove(&4), Err(Failure::wipe_out(var.id())));
+}
+
+#[test]
+fn interval_hole_ranges() {
+    let var = IntervalVar::<i64>::with_domain([0, 50_000_000]);
+    assert_eq!(var.size(), 2);
+    assert_eq!(var.holes().len(), 1);
+    assert!(!var.contains(&25_000_000));
+
+    let mut var = IntervalVar::<i64>::with_domain([30, 0, 10, 20]);
+    assert_eq!(var.holes().len(), 3);
+    assert_eq!(var.possibilities().collect::<Vec<_>>(), vec![0, 10, 20, 30]);
+    var.remove_range(&5, &25).unwrap();
+    assert_eq!(var.holes().len(), 1);
+    assert_eq!(var.possibilities().collect::<Vec<_>>(), vec![0, 30]);
+
+    let mut var = IntervalVar::<i64>::new(0, 99_999_999);
+    var.remove_range(&10, &20_000_000).unwrap();
+    var.remove(&9).unwrap();
+    var.remove(&20_000_001).unwrap();
+    var.remove(&30_000_000).unwrap();
+    assert_eq!(var.holes().len(), 2);
+    assert_eq!(var.size(), 100_000_000 - 19_999_994);
+    assert!(var.contains(&8));
+    assert!(!var.contains(&15));
+
+    var.remove_below(&100).unwrap();
+    assert_eq!(*var.min(), 20_000_002);
+    assert_eq!(var.holes().len(), 1);
+    var.remove_above(&30_000_000).unwrap();
+    assert_eq!(*var.max(), 29_999_999);
+    assert!(var.holes().is_empty());
+    assert_eq!(var.size(), 9_999_998);
+}
+
+#[test]
+fn interval_model() {
+    let mut model = Model::<IntervalVar<i32>>::new();
+    let vars = model.create_var_array(0..3, 3);
+    model.all_different(&vars, ValueElimination);
+    assert_eq!(model.solve().count(), 6);
+}
+
+#[test]
+fn bitset_domain() {
+    let mut var = BitSetVar::<i32>::with_domain([-3, 0, 5, 64, 100]);
+    assert_eq!(var.size(), 5);
+    assert!(var.contains(&64));
+    assert!(!var.contains(&1));
+    assert!(!var.contains(&1000));
+    assert_eq!(
+        var.possibilities().collect::<Vec<_>>(),
+        vec![-3, 0, 5, 64, 100]
+    );
+
+    var.remove(&-3).unwrap();
+    var.remove(&100).unwrap();
+    var.remove(&5).unwrap();
+    assert_eq!(var.possibilities().collect::<Vec<_>>(), vec![0, 64]);
+
+    var.instantiate(&64).unwrap();
+    assert_eq!(var.value(), Some(&64));
+    assert!(var.remove(&64).is_err());
+}
+
+#[test]
+fn bitset_model() {
+    let mut model = Model::<BitSetVar<u8>>::new();
+    let vars = model.create_var_array(1..5, 4);
+    model.all_different(&vars, ValueElimination);
+    assert_eq!(model.solve().count(), 24);
+}
+
+fn check_bounds<V>()
+where
+    V: OrderedVariable<Value = i32>,
+{
+    let mut var = V::with_domain(0..20);
+    assert_eq!((*var.min(), *var.max()), (0, 19));
+
+    assert_eq!(var.remove_below(&3), Ok(DomainUpdate::LowerBound(var.id())));
+    assert_eq!(var.remove_above(&15), Ok(DomainUpdate::UpperBound(var.id())));
+    assert_eq!(var.remove_below(&1), Ok(DomainUpdate::Unchanged(var.id())));
+    assert_eq!(var.remove_range(&5, &9), Ok(DomainUpdate::Reduced(var.id())));
+    assert_eq!(var.size(), 8);
+    assert!(!var.contains(&7));
+    assert_eq!(var.remove(&7), Ok(DomainUpdate::Unchanged(var.id())));
+    assert_eq!(var.remove_range(&9, &5), Ok(DomainUpdate::Unchanged(var.id())));
+
+    assert_eq!(var.remove_range(&0, &6), Ok(DomainUpdate::LowerBound(var.id())));
+    assert_eq!(*var.min(), 10);
+    assert_eq!(var.restrict_to(&11, &14), Ok(DomainUpdate::Bounds(var.id())));
+    assert_eq!(var.possibilities().collect::<Vec<_>>(), vec![11, 12, 13, 14]);
+    assert_eq!(var.remove(&14), Ok(DomainUpdate::UpperBound(var.id())));
+    assert_eq!(var.restrict_to(&13, &100), Ok(DomainUpdate::Fixed(var.id())));
+    assert_eq!(var.value(), Some(&13));
+
+    assert!(var.clone().remove_below(&14).is_err());
+    assert!(var.clone().remove_above(&12).is_err());
+    assert!(var.clone().remove_range(&0, &13).is_err());
+    assert!(var.clone().restrict_to(&0, &12).is_err());
+}
+
+#[test]
+fn bounds_operations() {
+    check_bounds::<BTreeSetVar<i32>>();
+    check_bounds::<IntervalVar<i32>>();
+    check_bounds::<BitSetVar<i32>>();
+}